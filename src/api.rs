@@ -0,0 +1,170 @@
+use std::{path::Path, sync::Arc};
+
+use console::style;
+use serde::Serialize;
+use snafu::ResultExt;
+
+use crate::{
+    consts::API_PATH,
+    context::{get_context, TimugContext},
+    engine::{RenderEngine, Renderable},
+    error::{FolderCreationFailedSnafu, JsonSerializationFailedSnafu},
+    feed::{absolute_links, absolute_url, post_authors, JsonFeedAuthor},
+    language::LanguageContext,
+    page::Page,
+    post::Post,
+    search::{plain_text, summary},
+    tools::get_file_name,
+};
+
+const SUMMARY_LENGTH: usize = 200;
+
+#[derive(Debug, Serialize)]
+pub struct ApiPost {
+    pub slug: String,
+    pub title: String,
+    pub url: String,
+    pub path: String,
+    pub date: String,
+    pub lang: String,
+    pub tags: Vec<String>,
+    pub authors: Vec<JsonFeedAuthor>,
+    pub summary: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+}
+
+impl ApiPost {
+    fn new(ctx: &TimugContext, post: &Post, content_html: Option<String>) -> Self {
+        let markdown = ctx.config.markdown.merge(&post.markdown());
+        Self {
+            slug: post.slug().clone(),
+            title: post.title().clone(),
+            url: absolute_url(&ctx.config.site_url, &post.url()),
+            path: post.url(),
+            date: post.date().to_rfc3339(),
+            lang: post.lang(),
+            tags: post.tags(),
+            authors: post_authors(ctx, post),
+            summary: summary(&plain_text(&post.content(), &markdown), SUMMARY_LENGTH),
+            content_html,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiTag {
+    pub name: String,
+    pub url: String,
+    pub count: usize,
+    pub posts: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiPage {
+    pub slug: String,
+    pub title: String,
+    pub url: String,
+    pub path: String,
+    pub lang: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+}
+
+impl ApiPage {
+    fn new(ctx: &TimugContext, page: &Page, content_html: Option<String>) -> Self {
+        Self {
+            slug: page.slug.clone(),
+            title: page.title.clone(),
+            url: absolute_url(&ctx.config.site_url, &page.url()),
+            path: page.url(),
+            lang: page.lang.clone().unwrap_or_default(),
+            content_html,
+        }
+    }
+}
+
+// Static json api of a language, list files have the metadata and the document files also have the rendered html
+pub struct Api<'a> {
+    pub language: &'a LanguageContext,
+    pub pages: Vec<Arc<Page>>,
+}
+
+impl Api<'_> {
+    fn write<T: Serialize>(engine: &RenderEngine<'_>, value: &T, file_name: &Path) -> crate::Result<()> {
+        if let Some(folder) = file_name.parent() {
+            std::fs::create_dir_all(folder).context(FolderCreationFailedSnafu { path: folder })?;
+        }
+
+        let content = serde_json::to_string(value).context(JsonSerializationFailedSnafu)?;
+        engine.write(content, &file_name.to_path_buf())
+    }
+}
+
+impl Renderable for Api<'_> {
+    type Context = ();
+    fn render(&self, engine: &RenderEngine<'_>, _: Self::Context) -> crate::Result<()> {
+        let ctx = get_context(snafu::location!())?;
+        let folder = ctx
+            .config
+            .blog_path
+            .join(&ctx.config.deployment_folder)
+            .join(
+                self.language
+                    .language
+                    .url(&format!("/{}", API_PATH))
+                    .trim_start_matches('/'),
+            );
+        engine.update_status(style("Rendering api").bold().cyan().to_string(), get_file_name(&folder)?.as_str());
+
+        let posts = &self.language.posts;
+        let list: Vec<ApiPost> = posts
+            .posts
+            .iter()
+            .map(|post| ApiPost::new(&ctx, post, None))
+            .collect();
+        Self::write(engine, &list, &folder.join("posts.json"))?;
+
+        // Unlisted posts are not in the list but they can be requested by their slugs
+        for post in posts.posts.iter().chain(posts.unlisted.iter()) {
+            let item = ApiPost::new(&ctx, post, Some(absolute_links(&ctx.config.site_url, &engine.content(&post.url())?)));
+            Self::write(
+                engine,
+                &item,
+                &folder
+                    .join("posts")
+                    .join(format!("{}.json", post.slug().as_str())),
+            )?;
+        }
+
+        let tags: Vec<ApiTag> = self
+            .language
+            .tags
+            .iter()
+            .map(|tag| ApiTag {
+                name: tag.name.clone(),
+                url: absolute_url(&ctx.config.site_url, &self.language.language.url(&tag.url())),
+                count: tag.posts.len(),
+                posts: tag.posts.iter().map(|post| post.slug().clone()).collect(),
+            })
+            .collect();
+        Self::write(engine, &tags, &folder.join("tags.json"))?;
+
+        let list: Vec<ApiPage> = self
+            .pages
+            .iter()
+            .map(|page| ApiPage::new(&ctx, page, None))
+            .collect();
+        Self::write(engine, &list, &folder.join("pages.json"))?;
+
+        for page in self.pages.iter() {
+            let item = ApiPage::new(&ctx, page, Some(absolute_links(&ctx.config.site_url, &engine.content(&page.url())?)));
+            Self::write(engine, &item, &folder.join("pages").join(format!("{}.json", page.slug)))?;
+        }
+
+        engine.update_status(style("Generated api").bold().green().to_string(), get_file_name(&folder)?.as_str());
+        Ok(())
+    }
+}
@@ -1,22 +1,23 @@
+use crate::check_links::check_links;
 use crate::cli::TemplateCommand;
 use crate::config::TimugConfig;
 use crate::consts::{ExamplesAssets, TemplateAssets, ASSETS_PATH, CONFIG_FILE_NAME, DEFAULT_DEPLOYMENT_FOLDER, DEFAULT_LANGUAGE, DEFAULT_THEME, PAGES_PATH, POSTS_PATH, ROCKET, TEMPLATES_PATH};
 use crate::context::{build_context, get_context};
-use crate::error::{CanceledByTheUserSnafu, CannotOverwriteConfigSnafu, CouldNotGetAbsolutePathSnafu, FileCreationFailedSnafu, NoCurrentDirSnafu, WriteSnafu, YamlSerializationFailedSnafu};
+use crate::error::{CanceledByTheUserSnafu, CannotOverwriteConfigSnafu, CouldNotGetAbsolutePathSnafu, FileCreationFailedSnafu, FolderCreationFailedSnafu, NoCurrentDirSnafu, WriteSnafu, YamlSerializationFailedSnafu};
 use crate::server::start_webserver;
 use crate::tools::{get_slug, inner_deploy_pages};
 
 use std::{
     fs::{create_dir, create_dir_all, File},
     io::Write,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use console::{style, Term};
 use snafu::{ensure, ResultExt};
 
-fn initialize(path: Option<PathBuf>, draft: bool) -> crate::Result<()> {
-    build_context(path, draft)?;
+fn initialize(path: Option<PathBuf>, draft: bool, preview: bool) -> crate::Result<()> {
+    build_context(path, draft, preview)?;
     Ok(())
 }
 
@@ -51,11 +52,7 @@ pub fn start_create_new_project(project_path: Option<PathBuf>) -> crate::Result<
 
         match answer {
             Ok('Y') | Ok('y') => (),
-            _ => {
-                return CanceledByTheUserSnafu
-                    .fail()
-                    .map_err(::core::convert::Into::into)
-            }
+            _ => return CanceledByTheUserSnafu.fail(),
         }
     }
 
@@ -78,13 +75,7 @@ pub fn start_create_new_project(project_path: Option<PathBuf>) -> crate::Result<
     let _ = create_dir(config.blog_path.join(ASSETS_PATH));
     let _ = create_dir(config.blog_path.join(DEFAULT_DEPLOYMENT_FOLDER));
 
-    for file_name in TemplateAssets::iter() {
-        if let Some(file_content) = TemplateAssets::get(&file_name) {
-            let mut file = File::create(template_path.join(file_name.to_string())).context(FileCreationFailedSnafu { path: template_path.join(file_name.to_string()) })?;
-            file.write_all(&file_content.data)
-                .context(WriteSnafu { path: template_path.join(file_name.to_string()) })?;
-        }
-    }
+    write_template_assets(&template_path)?;
 
     for file_name in ExamplesAssets::iter() {
         if let Some(file_content) = ExamplesAssets::get(&file_name) {
@@ -103,7 +94,7 @@ pub fn start_create_new_project(project_path: Option<PathBuf>) -> crate::Result<
 }
 
 pub fn start_server(path: Option<PathBuf>, port: Option<u16>, draft: bool) -> crate::Result<()> {
-    initialize(path.clone(), draft)?;
+    initialize(path.clone(), draft, false)?;
     log::info!("Building...");
     inner_deploy_pages()?;
     log::info!("Starting webserver...");
@@ -111,10 +102,29 @@ pub fn start_server(path: Option<PathBuf>, port: Option<u16>, draft: bool) -> cr
     Ok(())
 }
 
-pub fn start_deploy_pages(path: Option<PathBuf>, draft: bool) -> crate::Result<()> {
-    initialize(path.clone(), draft)?;
+pub fn start_deploy_pages(path: Option<PathBuf>, draft: bool, preview: bool) -> crate::Result<()> {
+    initialize(path.clone(), draft, preview)?;
     log::info!("Deployment started");
-    inner_deploy_pages()
+    inner_deploy_pages()?;
+
+    let ctx = get_context(snafu::location!())?;
+    if !ctx.posts.previews.is_empty() {
+        log::info!("Preview links:");
+        for post in ctx.posts.previews.iter() {
+            log::info!("{}: {}{}", style(post.title().as_str()).yellow(), ctx.config.site_url.trim_end_matches('/'), post.url());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn start_check_links(path: Option<PathBuf>, draft: bool) -> crate::Result<()> {
+    initialize(path.clone(), draft, false)?;
+    log::info!("Deployment started");
+    inner_deploy_pages()?;
+
+    let ctx = get_context(snafu::location!())?;
+    check_links(ctx.config.blog_path.join(&ctx.config.deployment_folder), &ctx.config.site_url)
 }
 
 pub fn create_page(path: Option<PathBuf>, title: String, draft: bool) -> crate::Result<()> {
@@ -126,7 +136,7 @@ pub fn create_post(path: Option<PathBuf>, title: String, draft: bool) -> crate::
 }
 
 fn create_new(path: Option<PathBuf>, title: String, draft: bool, folder: &str) -> crate::Result<()> {
-    initialize(path.clone(), draft)?;
+    initialize(path.clone(), draft, false)?;
     let ctx = get_context(snafu::location!())?;
     let slug = get_slug(&title);
     let date = chrono::offset::Local::now().format("%Y-%m-%d %H:%M:%S");
@@ -161,7 +171,7 @@ tags:
 }
 
 pub fn execute_template(path: Option<PathBuf>, command: TemplateCommand) -> crate::Result<()> {
-    initialize(path.clone(), false)?;
+    initialize(path.clone(), false, false)?;
     let ctx = get_context(snafu::location!())?;
     let template_path = ctx
         .config
@@ -171,13 +181,7 @@ pub fn execute_template(path: Option<PathBuf>, command: TemplateCommand) -> crat
 
     match command {
         TemplateCommand::Upgrade => {
-            for file_name in TemplateAssets::iter() {
-                if let Some(file_content) = TemplateAssets::get(&file_name) {
-                    let mut file = File::create(template_path.join(file_name.to_string())).context(FileCreationFailedSnafu { path: template_path.join(file_name.to_string()) })?;
-                    file.write_all(&file_content.data)
-                        .context(WriteSnafu { path: template_path.join(file_name.to_string()) })?;
-                }
-            }
+            write_template_assets(&template_path)?;
 
             log::warn!("Template updated.");
         }
@@ -185,3 +189,21 @@ pub fn execute_template(path: Option<PathBuf>, command: TemplateCommand) -> crat
     };
     Ok(())
 }
+
+// Theme files can be located in sub folders, such as the i18n translations
+fn write_template_assets(template_path: &Path) -> crate::Result<()> {
+    for file_name in TemplateAssets::iter() {
+        if let Some(file_content) = TemplateAssets::get(&file_name) {
+            let path = template_path.join(file_name.to_string());
+            if let Some(folder) = path.parent() {
+                create_dir_all(folder).context(FolderCreationFailedSnafu { path: folder })?;
+            }
+
+            let mut file = File::create(&path).context(FileCreationFailedSnafu { path: path.clone() })?;
+            file.write_all(&file_content.data)
+                .context(WriteSnafu { path })?;
+        }
+    }
+
+    Ok(())
+}
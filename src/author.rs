@@ -0,0 +1,100 @@
+use std::{path::PathBuf, sync::Arc};
+
+use console::style;
+use minijinja::{context, value::Object, Value};
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::{
+    config::AuthorInfo,
+    engine::{RenderEngine, Renderable},
+    error::{MarkdownRenderFailedSnafu, MarkdownTemplateNotFoundSnafu},
+    feed::Feed,
+    post::Post,
+    posts::Posts,
+    tools::{get_file_name, url_encode},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Author {
+    pub id: String,
+    pub info: AuthorInfo,
+    pub posts: Vec<Arc<Post>>,
+}
+
+pub struct AuthorContext {
+    pub folder: PathBuf,
+    pub index: usize,
+    pub lang: String,
+    pub template_path: String,
+}
+
+impl Author {
+    pub fn url(&self) -> String {
+        format!("/authors/{}.html", url_encode(self.id.clone()))
+    }
+
+    pub fn feed_url(&self) -> String {
+        format!("/authors/{}.xml", url_encode(self.id.clone()))
+    }
+}
+
+impl Renderable for Author {
+    type Context = AuthorContext;
+    fn render(&self, engine: &RenderEngine<'_>, ctx: AuthorContext) -> crate::Result<()> {
+        let context = engine.create_context(&ctx.lang)?;
+        let file_name = ctx
+            .folder
+            .join(format!("{}.html", url_encode(self.id.clone())));
+        engine.update_status(style("Rendering author").bold().cyan().to_string(), get_file_name(&file_name)?.as_str());
+
+        let posts = Value::from_object(Posts { posts: self.posts.clone(), ..Default::default() });
+
+        let template = engine
+            .env
+            .get_template(&ctx.template_path)
+            .context(MarkdownTemplateNotFoundSnafu { template: &ctx.template_path })?;
+        let context = context! {
+            ..context! { index => ctx.index, posts => posts, author => Value::from_object(self.clone()), current_url => self.url() },
+            ..context.clone()
+        };
+
+        let content = template
+            .render(context)
+            .context(MarkdownRenderFailedSnafu { file_name: file_name.clone() })?;
+        engine.write(content, &file_name)?;
+
+        let feed = Feed {
+            title: self.info.name.clone(),
+            lang: ctx.lang.clone(),
+            url: self.feed_url(),
+            posts: self.posts.clone(),
+        };
+        feed.render(
+            engine,
+            ctx.folder
+                .join(format!("{}.xml", url_encode(self.id.clone()))),
+        )?;
+
+        engine.update_status(style("Generated author").bold().green().to_string(), get_file_name(&file_name)?.as_str());
+        Ok(())
+    }
+}
+
+impl Object for Author {
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        let key = key.as_str()?;
+        match key {
+            "id" => Some(Value::from(self.id.as_str())),
+            "name" => Some(Value::from(self.info.name.as_str())),
+            "bio" => self.info.bio.as_deref().map(Value::from),
+            "avatar" => self.info.avatar.as_deref().map(Value::from),
+            "email" => self.info.email.as_deref().map(Value::from),
+            "links" => Some(Value::from_serialize(&self.info.links)),
+            "url" => Some(Value::from(self.url())),
+            "feed_url" => Some(Value::from(self.feed_url())),
+            "posts" => Some(Value::from_iter(self.posts.iter().cloned().map(Value::from_dyn_object))),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::{collections::HashMap, sync::Arc};
+
+use minijinja::{
+    value::{Enumerator, Object},
+    Value,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{author::Author, config::AuthorInfo, post::Post, posts::Posts};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Authors {
+    pub authors: Vec<Arc<Author>>,
+}
+
+impl Authors {
+    pub fn new(infos: &HashMap<String, AuthorInfo>) -> Self {
+        let mut authors: Vec<Arc<Author>> = infos
+            .iter()
+            .map(|(id, info)| Arc::new(Author { id: id.clone(), info: info.clone(), posts: Vec::new() }))
+            .collect();
+
+        authors.sort_by(|x, y| x.info.name.cmp(&y.info.name));
+        Self { authors }
+    }
+
+    pub fn add(&mut self, id: &str, post: Arc<Post>) -> Option<()> {
+        let author = self.authors.iter_mut().find(|author| author.id == id)?;
+        Arc::make_mut(author).posts.push(post);
+        Some(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Arc<Author>> {
+        self.authors.iter().find(|author| author.id == id)
+    }
+
+    pub fn sort_posts(&mut self) {
+        for author in self.authors.iter_mut() {
+            Posts::sort(&mut Arc::make_mut(author).posts);
+        }
+    }
+}
+
+impl Object for Authors {
+    fn repr(self: &Arc<Self>) -> minijinja::value::ObjectRepr {
+        minijinja::value::ObjectRepr::Seq
+    }
+
+    fn get_value(self: &Arc<Self>, index: &Value) -> Option<Value> {
+        let item = self.authors.get(index.as_usize()?)?;
+        Some(Value::from_dyn_object(item.clone()))
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        Enumerator::Seq(self.authors.len())
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    path::{Component, Path, PathBuf},
+};
+
+use scraper::{Html, Selector};
+use snafu::ensure;
+
+use crate::{
+    error::BrokenLinksFoundSnafu,
+    tools::{get_file_content, get_files_recursive},
+};
+
+// Element attributes that point to other files of the site
+const LINK_ATTRIBUTES: [(&str, &str); 9] = [("a", "href"), ("area", "href"), ("link", "href"), ("img", "src"), ("script", "src"), ("source", "src"), ("iframe", "src"), ("video", "src"), ("audio", "src")];
+const SRCSET_ELEMENTS: [&str; 2] = ["img", "source"];
+
+// Fragments that browsers handle without an element
+const IMPLICIT_ANCHORS: [&str; 1] = ["top"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BrokenReason {
+    MissingFile,
+    MissingAnchor,
+    OutsideOfSite,
+}
+
+impl Display for BrokenReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BrokenReason::MissingFile => write!(f, "file not found"),
+            BrokenReason::MissingAnchor => write!(f, "anchor not found"),
+            BrokenReason::OutsideOfSite => write!(f, "outside of the site folder"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokenLink {
+    pub link: String,
+    pub reason: BrokenReason,
+}
+
+struct Document {
+    links: Vec<String>,
+    anchors: HashSet<String>,
+}
+
+impl Document {
+    fn parse(content: &str) -> Self {
+        let html = Html::parse_document(content);
+        let mut links = Vec::new();
+
+        for (element, attribute) in LINK_ATTRIBUTES.iter() {
+            let selector = Selector::parse(&format!("{}[{}]", element, attribute)).expect("valid selector");
+            links.extend(
+                html.select(&selector)
+                    .filter_map(|node| node.value().attr(attribute))
+                    .map(|link| link.trim().to_string()),
+            );
+        }
+
+        for element in SRCSET_ELEMENTS.iter() {
+            let selector = Selector::parse(&format!("{}[srcset]", element)).expect("valid selector");
+            for srcset in html
+                .select(&selector)
+                .filter_map(|node| node.value().attr("srcset"))
+            {
+                links.extend(
+                    srcset
+                        .split(',')
+                        .filter_map(|candidate| candidate.split_whitespace().next())
+                        .map(|link| link.to_string()),
+                );
+            }
+        }
+
+        let selector = Selector::parse("[id], a[name]").expect("valid selector");
+        let anchors = html
+            .select(&selector)
+            .flat_map(|node| [node.value().id(), node.value().attr("name")])
+            .flatten()
+            .map(|anchor| anchor.to_string())
+            .collect();
+
+        Self { links, anchors }
+    }
+}
+
+pub struct LinkChecker {
+    root: PathBuf,
+    site_url: String,
+}
+
+impl LinkChecker {
+    pub fn new(root: PathBuf, site_url: &str) -> Self {
+        Self {
+            root,
+            site_url: site_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    // Absolute links of the site are checked like the root-relative ones, other external links are skipped
+    fn local_link<'a>(&self, link: &'a str) -> Option<&'a str> {
+        if link.is_empty() || link == "#" {
+            return None;
+        }
+
+        if !self.site_url.is_empty() {
+            if let Some(path) = link.strip_prefix(self.site_url.as_str()) {
+                return match path.is_empty() {
+                    true => Some("/"),
+                    false => path.starts_with(['/', '#', '?']).then_some(path),
+                };
+            }
+        }
+
+        let scheme = link
+            .split_once(':')
+            .is_some_and(|(scheme, _)| !scheme.contains(['/', '?', '#']));
+        match scheme || link.starts_with("//") {
+            true => None,
+            false => Some(link),
+        }
+    }
+
+    // Output file of the link, "." and ".." parts are resolved without leaving the site folder
+    fn target_path(&self, source: &Path, path: &str) -> Option<PathBuf> {
+        let (base, path) = match path.strip_prefix('/') {
+            Some(path) => (PathBuf::new(), path),
+            None => (source.parent().map(Path::to_path_buf).unwrap_or_default(), path),
+        };
+
+        let decoded = urlencoding::decode(path).ok()?;
+        let mut target = base;
+        for component in Path::new(decoded.as_ref()).components() {
+            match component {
+                Component::Normal(part) => target.push(part),
+                Component::ParentDir => target.pop().then_some(())?,
+                _ => (),
+            }
+        }
+
+        if path.is_empty() || path.ends_with('/') || self.root.join(&target).is_dir() {
+            target.push("index.html");
+        }
+        Some(target)
+    }
+
+    fn check_link(&self, source: &Path, link: &str, documents: &HashMap<PathBuf, Document>) -> Option<BrokenReason> {
+        let link = self.local_link(link)?;
+        let (link, fragment) = match link.split_once('#') {
+            Some((link, fragment)) => (link, Some(fragment)),
+            None => (link, None),
+        };
+        let path = link.split('?').next().unwrap_or_default();
+
+        let target = match path.is_empty() {
+            true => source.to_path_buf(),
+            false => match self.target_path(source, path) {
+                Some(target) => target,
+                None => return Some(BrokenReason::OutsideOfSite),
+            },
+        };
+
+        if !self.root.join(&target).is_file() {
+            return Some(BrokenReason::MissingFile);
+        }
+
+        let fragment = urlencoding::decode(fragment.unwrap_or_default()).ok()?;
+        match documents.get(&target) {
+            Some(document) if !fragment.is_empty() && !IMPLICIT_ANCHORS.contains(&fragment.as_ref()) && !document.anchors.contains(fragment.as_ref()) => Some(BrokenReason::MissingAnchor),
+            _ => None,
+        }
+    }
+
+    // Broken links of every generated html file, grouped by the file path relative to the site folder
+    pub fn check(&self) -> crate::Result<BTreeMap<String, Vec<BrokenLink>>> {
+        let mut documents = HashMap::new();
+        for file in get_files_recursive(&self.root, "html")? {
+            let content = get_file_content(&file)?;
+            let path = file.strip_prefix(&self.root).unwrap_or(&file).to_path_buf();
+            documents.insert(path, Document::parse(&content));
+        }
+
+        let mut result = BTreeMap::new();
+        for (path, document) in documents.iter() {
+            let mut broken = Vec::new();
+            for link in document.links.iter() {
+                if let Some(reason) = self.check_link(path, link, &documents) {
+                    let broken_link = BrokenLink { link: link.clone(), reason };
+                    if !broken.contains(&broken_link) {
+                        broken.push(broken_link);
+                    }
+                }
+            }
+
+            if !broken.is_empty() {
+                result.insert(path.to_string_lossy().replace('\\', "/"), broken);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+pub fn check_links(root: PathBuf, site_url: &str) -> crate::Result<()> {
+    log::info!("Checking links: {}", root.display());
+    let result = LinkChecker::new(root, site_url).check()?;
+
+    for (source, links) in result.iter() {
+        log::error!("{}", source);
+        for link in links.iter() {
+            log::error!("    {} ({})", link.link, link.reason);
+        }
+    }
+
+    let count: usize = result.values().map(Vec::len).sum();
+    ensure!(count == 0, BrokenLinksFoundSnafu { count, documents: result.len() });
+
+    log::info!("No broken links found");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_check() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("2024").join("1")).unwrap();
+        std::fs::create_dir_all(dir.path().join("assets")).unwrap();
+        std::fs::write(dir.path().join("assets").join("photo.png"), "").unwrap();
+        std::fs::write(
+            dir.path().join("index.html"),
+            r##"<a href="/2024/1/post.html#intro">Post</a><a href="https://example.com/2024/1/post.html">Post</a><a href="https://github.com">External</a>
+            <a href="#">Top</a><a href="mailto:me@example.com">Mail</a><img src="assets/photo.png" srcset="/assets/photo.png 1x, /assets/photo-2x.png 2x">"##,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("2024").join("1").join("post.html"),
+            r##"<h2 id="intro">Intro</h2><a href="../../index.html">Home</a><a href="/">Home</a><a href="#missing">Missing</a><a href="../../../outside.html">Outside</a><a href="/about.html?x=1">About</a><a href="#top">Top</a>"##,
+        )
+        .unwrap();
+
+        let result = LinkChecker::new(dir.path().to_path_buf(), "https://example.com/")
+            .check()
+            .unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result["index.html"],
+            vec![BrokenLink {
+                link: "/assets/photo-2x.png".to_string(),
+                reason: BrokenReason::MissingFile
+            }]
+        );
+        assert_eq!(
+            result["2024/1/post.html"],
+            vec![
+                BrokenLink {
+                    link: "#missing".to_string(),
+                    reason: BrokenReason::MissingAnchor
+                },
+                BrokenLink {
+                    link: "../../../outside.html".to_string(),
+                    reason: BrokenReason::OutsideOfSite
+                },
+                BrokenLink {
+                    link: "/about.html?x=1".to_string(),
+                    reason: BrokenReason::MissingFile
+                },
+            ]
+        );
+    }
+}
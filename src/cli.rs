@@ -80,6 +80,17 @@ pub enum Commands {
         /// Deploy draft posts
         #[arg(short, long, action = clap::ArgAction::SetTrue)]
         draft: bool,
+
+        /// Deploy draft posts under secret preview links
+        #[arg(long, action = clap::ArgAction::SetTrue)]
+        preview: bool,
+    },
+
+    /// Generate static pages and check the links of the generated files
+    CheckLinks {
+        /// Deploy draft posts
+        #[arg(short, long, action = clap::ArgAction::SetTrue)]
+        draft: bool,
     },
 
     /// Start development server with live update
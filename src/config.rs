@@ -1,15 +1,101 @@
 use std::{collections::HashMap, path::PathBuf};
 
+use pulldown_cmark::Options;
 use serde::{Deserialize, Serialize};
 
 use crate::consts::{DEFAULT_DEPLOYMENT_FOLDER, DEFAULT_LANGUAGE, DEFAULT_THEME};
 
-#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NavItem {
     pub name: String,
     pub link: String,
 }
 
+// Markdown parser extensions, unset values fall back to the site configuration and then to the defaults
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkdownOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tables: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footnotes: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strikethrough: Option<bool>,
+
+    #[serde(default, rename = "task-lists", skip_serializing_if = "Option::is_none")]
+    pub task_lists: Option<bool>,
+
+    #[serde(default, rename = "smart-punctuation", skip_serializing_if = "Option::is_none")]
+    pub smart_punctuation: Option<bool>,
+
+    #[serde(default, rename = "heading-attributes", skip_serializing_if = "Option::is_none")]
+    pub heading_attributes: Option<bool>,
+
+    #[serde(default, rename = "definition-lists", skip_serializing_if = "Option::is_none")]
+    pub definition_lists: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callouts: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub figures: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub math: Option<bool>,
+}
+
+impl MarkdownOptions {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn merge(&self, other: &MarkdownOptions) -> Self {
+        Self {
+            tables: other.tables.or(self.tables),
+            footnotes: other.footnotes.or(self.footnotes),
+            strikethrough: other.strikethrough.or(self.strikethrough),
+            task_lists: other.task_lists.or(self.task_lists),
+            smart_punctuation: other.smart_punctuation.or(self.smart_punctuation),
+            heading_attributes: other.heading_attributes.or(self.heading_attributes),
+            definition_lists: other.definition_lists.or(self.definition_lists),
+            callouts: other.callouts.or(self.callouts),
+            figures: other.figures.or(self.figures),
+            math: other.math.or(self.math),
+        }
+    }
+
+    // Callouts are enabled by default, so "> [!NOTE]" renders like on GitHub
+    pub fn callouts(&self) -> bool {
+        self.callouts.unwrap_or(true)
+    }
+
+    // Figures change the markup of the existing titled images, so they are only rendered when enabled
+    pub fn figures(&self) -> bool {
+        self.figures.unwrap_or_default()
+    }
+
+    pub fn math(&self) -> bool {
+        self.math.unwrap_or_default()
+    }
+
+    pub fn options(&self) -> Options {
+        let mut options = Options::empty();
+        options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+        options.insert(Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS);
+        options.set(Options::ENABLE_TABLES, self.tables.unwrap_or(true));
+        options.set(Options::ENABLE_FOOTNOTES, self.footnotes.unwrap_or(true));
+        options.set(Options::ENABLE_HEADING_ATTRIBUTES, self.heading_attributes.unwrap_or(true));
+        options.set(Options::ENABLE_STRIKETHROUGH, self.strikethrough.unwrap_or_default());
+        options.set(Options::ENABLE_TASKLISTS, self.task_lists.unwrap_or_default());
+        options.set(Options::ENABLE_SMART_PUNCTUATION, self.smart_punctuation.unwrap_or_default());
+        options.set(Options::ENABLE_DEFINITION_LIST, self.definition_lists.unwrap_or_default());
+        options.set(Options::ENABLE_GFM, self.callouts());
+        options.set(Options::ENABLE_MATH, self.math());
+        options
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct TimugConfig {
     pub title: String,
@@ -21,6 +107,9 @@ pub struct TimugConfig {
     #[serde(default = "default_language")]
     pub lang: String,
 
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub languages: HashMap<String, LanguageInfo>,
+
     #[serde(default = "default_theme")]
     pub theme: String,
 
@@ -29,9 +118,31 @@ pub struct TimugConfig {
 
     #[serde(default, rename = "site-url")]
     pub site_url: String,
+
+    #[serde(default, skip_serializing_if = "MarkdownOptions::is_empty")]
+    pub markdown: MarkdownOptions,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<ImagesConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search: Option<SearchConfig>,
+
+    // Static json files of the posts, tags and pages under the api folder
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub api: bool,
+
+    #[serde(default, rename = "preview-secret", skip_serializing_if = "Option::is_none")]
+    pub preview_secret: Option<String>,
+
+    #[serde(default, rename = "reference-date", skip_serializing_if = "Option::is_none")]
+    pub reference_date: Option<String>,
     pub author: String,
     pub email: String,
 
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub authors: HashMap<String, AuthorInfo>,
+
     #[serde(default)]
     pub contacts: Vec<Contact>,
 
@@ -42,6 +153,143 @@ pub struct TimugConfig {
     pub other: HashMap<String, serde_yaml::value::Value>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Webp,
+    Avif,
+}
+
+// Responsive image variants of the markdown images under the assets folder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImagesConfig {
+    #[serde(default = "default_image_widths")]
+    pub widths: Vec<u32>,
+
+    #[serde(default = "default_image_formats")]
+    pub formats: Vec<ImageFormat>,
+
+    #[serde(default = "default_image_sizes")]
+    pub sizes: String,
+
+    #[serde(default = "default_image_quality")]
+    pub quality: u8,
+
+    #[serde(default = "default_image_lazy")]
+    pub lazy: bool,
+}
+
+impl Default for ImagesConfig {
+    fn default() -> Self {
+        Self {
+            widths: default_image_widths(),
+            formats: default_image_formats(),
+            sizes: default_image_sizes(),
+            quality: default_image_quality(),
+            lazy: default_image_lazy(),
+        }
+    }
+}
+
+fn default_image_widths() -> Vec<u32> {
+    vec![480, 960, 1440]
+}
+
+fn default_image_formats() -> Vec<ImageFormat> {
+    vec![ImageFormat::Webp]
+}
+
+fn default_image_sizes() -> String {
+    "(max-width: 960px) 100vw, 960px".to_string()
+}
+
+fn default_image_quality() -> u8 {
+    80
+}
+
+fn default_image_lazy() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchField {
+    Title,
+    Url,
+    Tags,
+    Date,
+    Summary,
+    Body,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchCollection {
+    Posts,
+    Pages,
+}
+
+// Client-side search index of the posts and pages, generated for every language
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchConfig {
+    #[serde(default = "default_search_fields")]
+    pub fields: Vec<SearchField>,
+
+    #[serde(default = "default_search_collections")]
+    pub collections: Vec<SearchCollection>,
+
+    #[serde(default = "default_summary_length", rename = "summary-length")]
+    pub summary_length: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            fields: default_search_fields(),
+            collections: default_search_collections(),
+            summary_length: default_summary_length(),
+        }
+    }
+}
+
+fn default_search_fields() -> Vec<SearchField> {
+    vec![SearchField::Title, SearchField::Url, SearchField::Tags, SearchField::Date, SearchField::Summary, SearchField::Body]
+}
+
+fn default_search_collections() -> Vec<SearchCollection> {
+    vec![SearchCollection::Posts, SearchCollection::Pages]
+}
+
+fn default_summary_length() -> usize {
+    160
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageInfo {
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorInfo {
+    pub name: String,
+
+    #[serde(default)]
+    pub bio: Option<String>,
+
+    #[serde(default)]
+    pub avatar: Option<String>,
+
+    #[serde(default)]
+    pub email: Option<String>,
+
+    #[serde(default)]
+    pub links: Vec<NavItem>,
+}
+
 #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Contact {
     pub icon: String,
@@ -64,3 +312,26 @@ fn default_language() -> String {
 fn default_deployment_folder() -> PathBuf {
     default_blog_path().join(DEFAULT_DEPLOYMENT_FOLDER)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let options = MarkdownOptions { strikethrough: Some(true), ..Default::default() };
+        let site = MarkdownOptions {
+            strikethrough: Some(false),
+            tables: Some(false),
+            ..Default::default()
+        };
+        let merged = site.merge(&options);
+        assert_eq!(merged.strikethrough, Some(true));
+        assert_eq!(merged.tables, Some(false));
+        assert!(merged.options().contains(Options::ENABLE_STRIKETHROUGH));
+        assert!(!merged.options().contains(Options::ENABLE_TABLES));
+        assert!(MarkdownOptions::default()
+            .options()
+            .contains(Options::ENABLE_TABLES));
+    }
+}
@@ -18,12 +18,21 @@ pub const TEMPLATES_PATH: &str = "templates";
 pub const POSTS_PATH: &str = "posts";
 pub const PAGES_PATH: &str = "pages";
 pub const ASSETS_PATH: &str = "assets";
+pub const PREVIEW_PATH: &str = "preview";
+pub const I18N_PATH: &str = "i18n";
+pub const CACHE_PATH: &str = ".timug-cache";
+pub const RESIZED_IMAGES_PATH: &str = "resized";
+pub const API_PATH: &str = "api";
 pub const CONFIG_FILE_NAME: &str = "timug.yaml";
 pub const TEMPLATE_FILE_NAME: &str = "template.yaml";
+pub const JSON_FEED_FILE_NAME: &str = "feed.json";
+pub const SEARCH_INDEX_FILE_NAME: &str = "search.json";
 
+pub const INDEX_PAGE: &str = "index";
 pub const PAGE_HTML: &str = "page.html";
 pub const POST_HTML: &str = "post.html";
 pub const POSTS_HTML: &str = "posts.html";
+pub const AUTHOR_HTML: &str = "author.html";
 
 pub const ROCKET: Emoji<'_, '_> = Emoji("🚀 ", ":-)");
 pub const SPARKLE: Emoji<'_, '_> = Emoji("✨ ", "#");
@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::env::current_dir;
 use std::fs::read_to_string;
 use std::path::PathBuf;
@@ -10,16 +11,19 @@ use minijinja::Value;
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use serde::de::DeserializeOwned;
 use serde_yaml::from_str;
-use snafu::{OptionExt, ResultExt};
+use snafu::{ensure, OptionExt, ResultExt};
 
+use crate::authors::Authors;
 use crate::config::TimugConfig;
-use crate::consts::{ASSETS_PATH, CONFIG_FILE_NAME, PAGES_PATH, POSTS_PATH, TEMPLATES_PATH};
-use crate::error::{CanonicalizeSnafu, ContextNotInitializedSnafu, CurrentDirChangeSnafu, FileNotFoundSnafu, NoCurrentDirSnafu, YamlDeserializationFailedSnafu};
+use crate::consts::{ASSETS_PATH, CACHE_PATH, CONFIG_FILE_NAME, PAGES_PATH, POSTS_PATH, TEMPLATES_PATH};
+use crate::error::{CanonicalizeSnafu, ContextNotInitializedSnafu, CurrentDirChangeSnafu, FileNotFoundSnafu, NoCurrentDirSnafu, PreviewSecretNotConfiguredSnafu, YamlDeserializationFailedSnafu};
+use crate::i18n::{fallback_languages, Translations};
+use crate::language::{Language, LanguageContext};
 use crate::page::Page;
 use crate::pages::Pages;
 use crate::posts::Posts;
-use crate::tags::Tags;
 use crate::template::Template;
+use crate::wikilinks::Backlink;
 
 static CONTEXT: OnceLock<RwLock<TimugContext>> = OnceLock::new();
 
@@ -29,20 +33,26 @@ pub struct TimugContext {
     pub posts_path: PathBuf,
     pub pages_path: PathBuf,
     pub statics_path: PathBuf,
+    pub cache_path: PathBuf,
     pub headers: Vec<&'static str>,
     pub after_bodies: Vec<Cow<'static, str>>,
-    pub posts_value: Value,
+    pub authors_value: Value,
     pub pages_value: Value,
     pub pages: Arc<Pages>,
     pub posts: Arc<Posts>,
-    pub tags: Tags,
+    pub languages: Vec<Language>,
+    pub language_contexts: Vec<LanguageContext>,
+    pub translations: Translations,
+    pub backlinks: HashMap<String, Vec<Backlink>>,
+    pub authors: Arc<Authors>,
     pub template: Template,
     pub git_folder: Option<PathBuf>,
     pub draft: bool,
+    pub preview: bool,
 }
 
 impl TimugContext {
-    fn build(timug_path: Option<PathBuf>, draft: bool) -> crate::Result<Self> {
+    fn build(timug_path: Option<PathBuf>, draft: bool, preview: bool) -> crate::Result<Self> {
         let timug_path = match timug_path {
             Some(path) => match path.is_absolute() {
                 true => path,
@@ -59,6 +69,7 @@ impl TimugContext {
 
         let content = read_to_string(&config_path).context(FileNotFoundSnafu { path: config_path })?;
         let mut config: TimugConfig = from_str(&content).context(YamlDeserializationFailedSnafu { content })?;
+        ensure!(!preview || config.preview_secret.is_some(), PreviewSecretNotConfiguredSnafu);
 
         if !config.blog_path.is_absolute() {
             let tmp_path = timug_path.join(config.blog_path);
@@ -82,6 +93,7 @@ impl TimugContext {
         let posts_path = Self::get_path(&config, POSTS_PATH);
         let pages_path = Self::get_path(&config, PAGES_PATH);
         let statics_path = Self::get_path(&config, ASSETS_PATH);
+        let cache_path = Self::get_path(&config, CACHE_PATH);
 
         let current_dir = std::env::current_dir().context(NoCurrentDirSnafu)?;
         std::env::set_current_dir(&timug_path).context(CurrentDirChangeSnafu { path: timug_path })?;
@@ -104,21 +116,29 @@ impl TimugContext {
 
         std::env::set_current_dir(&current_dir).context(CurrentDirChangeSnafu { path: current_dir })?;
 
+        let languages = Language::all(&config);
+
         Ok(Self {
             config,
             template,
             posts_path,
             pages_path,
             statics_path,
+            cache_path,
             headers: Default::default(),
             after_bodies: Default::default(),
-            posts_value: Default::default(),
+            authors_value: Default::default(),
             pages_value: Default::default(),
-            tags: Default::default(),
+            languages,
+            language_contexts: Default::default(),
+            translations: Default::default(),
+            backlinks: Default::default(),
+            authors: Default::default(),
             pages: Default::default(),
             posts: Default::default(),
             git_folder,
             draft,
+            preview,
         })
     }
 
@@ -134,13 +154,27 @@ impl TimugContext {
         None
     }
 
+    pub fn get_language_context(&self, lang: &str) -> Option<&LanguageContext> {
+        self.language_contexts
+            .iter()
+            .find(|item| item.language.code == lang)
+    }
+
     pub fn get_template_page(&self, name: &str) -> Option<Arc<Page>> {
         self.pages.get(name)
     }
+
+    // Translation of the key for the document language, same fallbacks as the t template function
+    pub fn translate(&self, lang: &str, key: &str) -> String {
+        let languages = fallback_languages(Some(lang).filter(|lang| !lang.is_empty()), &self.config.lang);
+        self.translations
+            .translate(&languages, key, None, &[])
+            .unwrap_or_else(|| key.to_string())
+    }
 }
 
-pub fn build_context(config_path: Option<PathBuf>, draft: bool) -> crate::Result<()> {
-    let context = TimugContext::build(config_path, draft)?;
+pub fn build_context(config_path: Option<PathBuf>, draft: bool, preview: bool) -> crate::Result<()> {
+    let context = TimugContext::build(config_path, draft, preview)?;
     let _ = CONTEXT.set(context.into());
     Ok(())
 }
@@ -1,7 +1,7 @@
 use std::{path::PathBuf, str::FromStr};
 
 use console::style;
-use minijinja::context;
+use minijinja::{context, ErrorKind};
 use serde::Serialize;
 use snafu::ResultExt;
 
@@ -13,13 +13,15 @@ use crate::{
 
 use super::{Document, DocumentContext};
 
+const BODY_BLOCK: &str = "body";
+
 pub struct HtmlDocument;
 
 impl<T> Document<T> for HtmlDocument
 where
     T: Serialize,
 {
-    fn render(&self, engine: &RenderEngine<'_>, ctx: DocumentContext<T>) -> crate::Result<()> {
+    fn render(&self, engine: &RenderEngine<'_>, ctx: DocumentContext<T>) -> crate::Result<String> {
         let filename: String = get_file_name(ctx.source_file_path.as_path())?;
         let source_file_path_str = get_path(ctx.source_file_path.as_path())?;
 
@@ -29,7 +31,7 @@ where
             .context(MarkdownTemplateNotFoundSnafu { template: source_file_path_str })?;
         engine.update_status(style("Rendering as HTML").bold().cyan().to_string(), &filename);
 
-        let context = engine.create_context()?;
+        let context = engine.create_context(&ctx.lang)?;
         let context = context! {
             ..context! {
                 title => ctx.title.as_str(),
@@ -38,15 +40,22 @@ where
             },
             ..context.clone()
         };
-        let content: String = template
-            .render(context)
-            .context(MarkdownRenderFailedSnafu {
-                file_name: PathBuf::from_str(&filename).context(PathBufParseSnafu { path: filename.clone() })?,
-            })?;
+        let file_name = PathBuf::from_str(&filename).context(PathBufParseSnafu { path: filename.clone() })?;
+        let mut rendered = template
+            .render_captured(context)
+            .context(MarkdownRenderFailedSnafu { file_name: file_name.clone() })?;
+
+        // Body block is the content of the page without the layout, pages without the block have no layout
+        let body = match rendered.with_state_mut(|state| state.render_block(BODY_BLOCK)) {
+            Ok(body) => body,
+            Err(error) if error.kind() == ErrorKind::UnknownBlock => rendered.output().to_string(),
+            Err(error) => return Err(error).context(MarkdownRenderFailedSnafu { file_name }),
+        };
+        let content = rendered.into_output();
 
         engine.write(content, &ctx.target_file_path)?;
         engine.update_status(style("Generated as HTML").bold().green().to_string(), filename.as_str());
 
-        Ok(())
+        Ok(body)
     }
 }
@@ -8,7 +8,7 @@ use snafu::ResultExt;
 use crate::{
     engine::RenderEngine,
     error::{MarkdownRenderFailedSnafu, MarkdownTemplateNotFoundSnafu, PathBufParseSnafu},
-    tools::{get_file_content, get_file_name, parse_yaml},
+    tools::{get_file_content, get_file_name, render_markdown},
 };
 
 use super::{Document, DocumentContext};
@@ -19,10 +19,13 @@ impl<T> Document<T> for MarkdownDocument
 where
     T: Serialize,
 {
-    fn render(&self, engine: &RenderEngine<'_>, ctx: DocumentContext<T>) -> crate::Result<()> {
+    fn render(&self, engine: &RenderEngine<'_>, ctx: DocumentContext<T>) -> crate::Result<String> {
         let file_name = get_file_name(ctx.source_file_path.as_path())?;
 
-        let context = engine.create_context()?;
+        let context = context! {
+            markdown => ctx.markdown,
+            ..engine.create_context(&ctx.lang)?
+        };
         let mut content: String = get_file_content(&ctx.source_file_path)?;
 
         engine.update_status(style("Rendering as Markdown").bold().cyan().to_string(), file_name.as_str());
@@ -35,9 +38,8 @@ where
             content = content_tmp;
         }
 
-        let mut content_tmp = String::new();
-        let parsed = parse_yaml(content.as_str());
-        pulldown_cmark::html::push_html(&mut content_tmp, parsed);
+        let after_bodies = context.get_attr("after_bodies").unwrap_or_default();
+        let content_tmp = render_markdown(content.as_str(), &ctx.markdown, &ctx.lang, &file_name, &after_bodies)?;
 
         let template = engine
             .env
@@ -46,7 +48,7 @@ where
         let context = context! {
             ..context! {
                 title => ctx.title.as_str(),
-                content => content_tmp,
+                content => content_tmp.as_str(),
                 index => ctx.index,
                 data => ctx.data,
             },
@@ -60,6 +62,6 @@ where
             })?;
         engine.write(content, &ctx.target_file_path)?;
         engine.update_status(style("Generated as Markdown").bold().green().to_string(), file_name.as_str());
-        Ok(())
+        Ok(content_tmp)
     }
 }
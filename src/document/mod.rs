@@ -1,6 +1,7 @@
 mod html;
 mod markdown;
 
+use crate::config::MarkdownOptions;
 use crate::engine::RenderEngine;
 use html::HtmlDocument;
 use markdown::MarkdownDocument;
@@ -14,6 +15,8 @@ where
     pub source_file_path: PathBuf,
     pub target_file_path: PathBuf,
     pub template: String,
+    pub lang: String,
+    pub markdown: MarkdownOptions,
     pub title: String,
     pub index: usize,
     pub data: T,
@@ -23,7 +26,8 @@ pub trait Document<T>
 where
     T: Serialize,
 {
-    fn render(&self, engine: &RenderEngine<'_>, ctx: DocumentContext<T>) -> crate::Result<()>;
+    // Returns the rendered content of the document without the layout, the body block for the html documents
+    fn render(&self, engine: &RenderEngine<'_>, ctx: DocumentContext<T>) -> crate::Result<String>;
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -35,7 +39,7 @@ pub enum DocumentType {
 }
 
 impl DocumentType {
-    pub fn render<T>(&self, engine: &RenderEngine<'_>, ctx: DocumentContext<T>) -> crate::Result<()>
+    pub fn render<T>(&self, engine: &RenderEngine<'_>, ctx: DocumentContext<T>) -> crate::Result<String>
     where
         T: Serialize,
     {
@@ -1,18 +1,47 @@
 use std::{
+    collections::HashMap,
     fs::File,
     io::Write,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
-use minijinja::{context, path_loader, Environment, Value};
+use minijinja::{context, path_loader, Environment, ErrorKind, Value};
+use parking_lot::Mutex;
 use snafu::{OptionExt, ResultExt};
 use subprocess::{Exec, Redirection};
 
 use crate::{
-    consts::POSTS_HTML,
-    error::{CurrentDirChangeSnafu, DirectoryCopyFailedSnafu, FileCreationFailedSnafu, FolderCreationFailedSnafu, MarkdownTemplateAddFailedSnafu, NoCurrentDirSnafu, SubProcessSnafu, TemplateReferenceNotFoundSnafu, WriteSnafu},
-    extensions::{alertbox::AlertBox, analytics::Analytics, codeblock::Codeblock, contacts::Contacts, gist::Gist, info::Info, projects::Projects, quote::Quote, reading::Reading, social_media_share::SocialMediaShare, stats::Stats},
+    api::Api,
+    author::AuthorContext,
+    consts::{AUTHOR_HTML, I18N_PATH, JSON_FEED_FILE_NAME, POSTS_HTML, SEARCH_INDEX_FILE_NAME},
+    error::{
+        CurrentDirChangeSnafu, DirectoryCopyFailedSnafu, FileCreationFailedSnafu, FolderCreationFailedSnafu, LanguageNotFoundSnafu, LayoutTemplateFailedSnafu, MarkdownTemplateAddFailedSnafu, NoCurrentDirSnafu, RenderedContentNotFoundSnafu, SubProcessSnafu, TemplateReferenceNotFoundSnafu, WriteSnafu,
+    },
+    extensions::{
+        alertbox::AlertBox,
+        analytics::Analytics,
+        codeblock::{Codeblock, CSS as CODEBLOCK_CSS},
+        contacts::Contacts,
+        figure::Figure,
+        gallery::Gallery,
+        gist::Gist,
+        include_code::IncludeCode,
+        info::Info,
+        meta_tags::MetaTags,
+        projects::Projects,
+        quote::Quote,
+        reading::Reading,
+        search::Search,
+        social_media_share::SocialMediaShare,
+        stats::Stats,
+        structured_data::StructuredData,
+        PageAssets,
+    },
+    feed::JsonFeed,
+    highlight::HIGHLIGHT_CSS_FILE_NAME,
+    i18n::Translations,
+    language::LanguageContext,
 };
 
 use crate::{
@@ -20,8 +49,10 @@ use crate::{
     extensions::Extension,
     pages::Pages,
     posts::Posts,
+    search::SearchIndex,
     tag::TagContext,
     tools::get_path,
+    wikilinks::build_backlinks,
 };
 
 pub trait Renderable {
@@ -31,13 +62,29 @@ pub trait Renderable {
 
 pub struct RenderEngine<'a> {
     pub env: Environment<'a>,
+
+    // Rendered content of the posts and pages, keys are the urls
+    contents: Mutex<HashMap<String, String>>,
 }
 
 impl<'a> RenderEngine<'a> {
     pub fn new() -> Self {
         let env = Environment::new();
 
-        Self { env }
+        Self { env, contents: Default::default() }
+    }
+
+    pub fn set_content(&self, url: String, content: String) {
+        self.contents.lock().insert(url, content);
+    }
+
+    // Documents are rendered before the feeds and the api, a missing content is not written as an empty one
+    pub fn content(&self, url: &str) -> crate::Result<String> {
+        self.contents
+            .lock()
+            .get(url)
+            .cloned()
+            .context(RenderedContentNotFoundSnafu { url })
     }
 
     pub fn run(&mut self) -> crate::error::Result<()> {
@@ -60,25 +107,25 @@ impl<'a> RenderEngine<'a> {
     }
 
     pub fn build_pages(&mut self) -> crate::error::Result<()> {
-        self.clear_tags()?;
+        self.parse_translations()?;
         self.parse_posts()?;
         self.parse_pages()?;
+        self.parse_backlinks()?;
 
         self.generate_pages()?;
         self.generate_posts()?;
         self.generate_tags()?;
+        self.generate_authors()?;
+        self.generate_feeds()?;
+        self.generate_search_index()?;
+        self.generate_api()?;
 
         self.move_assets()?;
+        self.generate_highlight_css()?;
 
         Ok(())
     }
 
-    pub fn clear_tags(&mut self) -> crate::Result<()> {
-        let mut ctx = get_mut_context(snafu::location!())?;
-        ctx.tags.clear();
-        Ok(())
-    }
-
     pub fn update_status(&self, status: String, message: &str) {
         log::debug!("{}: {}", status, message);
     }
@@ -153,13 +200,26 @@ impl<'a> RenderEngine<'a> {
         Ok(())
     }
 
+    pub fn parse_translations(&mut self) -> crate::error::Result<()> {
+        log::debug!("Parse translations");
+        let mut ctx = get_mut_context(snafu::location!())?;
+        let folders = [ctx.template.path.join(I18N_PATH), ctx.config.blog_path.join(I18N_PATH)];
+        ctx.translations = Translations::load(&folders)?;
+        Ok(())
+    }
+
     pub fn parse_posts(&mut self) -> crate::error::Result<()> {
         log::debug!("Parse posts");
         let posts = Arc::new(Posts::load()?);
 
         let mut ctx = get_mut_context(snafu::location!())?;
-        ctx.posts_value = Value::from_dyn_object(posts.clone());
+        ctx.language_contexts = ctx
+            .languages
+            .iter()
+            .map(|language| LanguageContext::new(language.clone(), &posts))
+            .collect::<crate::Result<Vec<_>>>()?;
         ctx.posts = posts;
+        ctx.authors_value = Value::from_dyn_object(ctx.authors.clone());
         Ok(())
     }
 
@@ -183,23 +243,42 @@ impl<'a> RenderEngine<'a> {
         Ok(())
     }
 
-    pub fn create_context(&self) -> crate::Result<Value> {
+    pub fn parse_backlinks(&mut self) -> crate::error::Result<()> {
+        log::debug!("Parse backlinks");
+        let backlinks = build_backlinks(&*get_context(snafu::location!())?);
+
+        let mut ctx = get_mut_context(snafu::location!())?;
+        ctx.backlinks = backlinks;
+        Ok(())
+    }
+
+    pub fn create_context(&self, lang: &str) -> crate::Result<Value> {
         let ctx = get_context(snafu::location!())?;
+        let language = ctx
+            .get_language_context(lang)
+            .context(LanguageNotFoundSnafu { lang })?;
+
         Ok(context! {
             config => ctx.config,
             headers => ctx.headers,
-            after_bodies => ctx.after_bodies,
-            tags => ctx.tags,
-            posts => ctx.posts_value,
+            after_bodies => Value::from_object(PageAssets::new(&ctx.after_bodies)),
+            tags => language.tags,
+            authors => ctx.authors_value,
+            posts => language.posts_value,
             pages => ctx.pages_value,
             navs => ctx.config.navs,
+            lang => language.language.code,
+            url_prefix => Value::from_safe_string(language.language.prefix.clone()),
+            languages => ctx.languages,
         })
     }
 
     pub fn generate_posts(&mut self) -> crate::error::Result<()> {
         log::debug!("Generate posts");
         let ctx = get_context(snafu::location!())?;
-        ctx.posts.render(self, ())?;
+        for language in ctx.language_contexts.iter() {
+            language.posts.render(self, ())?;
+        }
 
         Ok(())
     }
@@ -209,21 +288,124 @@ impl<'a> RenderEngine<'a> {
         let ctx = get_context(snafu::location!())?;
 
         let deployment_folder = ctx.config.blog_path.join(&ctx.config.deployment_folder);
-        let file_path = deployment_folder.join("tags");
         let posts_page = ctx
             .pages
             .get(POSTS_HTML)
-            .context(TemplateReferenceNotFoundSnafu { name: POSTS_HTML.to_string() })?;
+            .context(TemplateReferenceNotFoundSnafu {
+                name: POSTS_HTML.to_string(),
+                document: "tags".to_string(),
+            })?;
+
+        for language in ctx.language_contexts.iter() {
+            let file_path = deployment_folder.join(language.language.url("/tags").trim_start_matches('/'));
+            std::fs::create_dir_all(&file_path).context(FolderCreationFailedSnafu { path: file_path.clone() })?;
+
+            for (index, tag) in language.tags.iter().enumerate() {
+                let ctx = TagContext {
+                    folder: file_path.clone(),
+                    index,
+                    lang: language.language.code.clone(),
+                    prefix: language.language.prefix.clone(),
+                    template_path: posts_page.path.clone(),
+                };
+
+                tag.render(self, ctx)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn generate_authors(&mut self) -> crate::error::Result<()> {
+        log::debug!("Generate authors");
+        let ctx = get_context(snafu::location!())?;
+
+        if ctx.authors.authors.is_empty() {
+            return Ok(());
+        }
+
+        let deployment_folder = ctx.config.blog_path.join(&ctx.config.deployment_folder);
+        let file_path = deployment_folder.join("authors");
+        let author_page = ctx
+            .pages
+            .get(AUTHOR_HTML)
+            .or_else(|| ctx.pages.get(POSTS_HTML))
+            .context(TemplateReferenceNotFoundSnafu {
+                name: AUTHOR_HTML.to_string(),
+                document: "authors".to_string(),
+            })?;
         std::fs::create_dir_all(&file_path).context(FolderCreationFailedSnafu { path: file_path.clone() })?;
 
-        for (index, tag) in ctx.tags.iter().enumerate() {
-            let ctx = TagContext {
+        for (index, author) in ctx.authors.authors.iter().enumerate() {
+            let ctx = AuthorContext {
                 folder: file_path.clone(),
                 index,
-                template_path: posts_page.path.clone(),
+                lang: ctx.config.lang.clone(),
+                template_path: author_page.path.clone(),
             };
 
-            tag.render(self, ctx)?;
+            author.render(self, ctx)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn generate_feeds(&mut self) -> crate::error::Result<()> {
+        log::debug!("Generate feeds");
+        let ctx = get_context(snafu::location!())?;
+
+        let deployment_folder = ctx.config.blog_path.join(&ctx.config.deployment_folder);
+        for language in ctx.language_contexts.iter() {
+            let json_feed = JsonFeed {
+                title: ctx.config.title.clone(),
+                lang: language.language.code.clone(),
+                url: language.language.url(&format!("/{}", JSON_FEED_FILE_NAME)),
+                home_url: language.language.url("/"),
+                posts: language.posts.posts.clone(),
+            };
+            json_feed.render(self, deployment_folder.join(json_feed.url.trim_start_matches('/')))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn generate_search_index(&mut self) -> crate::error::Result<()> {
+        let ctx = get_context(snafu::location!())?;
+        let Some(config) = ctx.config.search.clone() else {
+            return Ok(());
+        };
+
+        log::debug!("Generate search index");
+        let deployment_folder = ctx.config.blog_path.join(&ctx.config.deployment_folder);
+        for language in ctx.language_contexts.iter() {
+            let index = SearchIndex {
+                config: config.clone(),
+                posts: language.posts.posts.clone(),
+                pages: ctx.pages.published(&language.language.code, ctx.draft),
+            };
+
+            let url = language
+                .language
+                .url(&format!("/{}", SEARCH_INDEX_FILE_NAME));
+            index.render(self, deployment_folder.join(url.trim_start_matches('/')))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn generate_api(&mut self) -> crate::error::Result<()> {
+        let ctx = get_context(snafu::location!())?;
+        if !ctx.config.api {
+            return Ok(());
+        }
+
+        log::debug!("Generate api");
+        for language in ctx.language_contexts.iter() {
+            let api = Api {
+                language,
+                pages: ctx.pages.published(&language.language.code, ctx.draft),
+            };
+            api.render(self, ())?;
         }
 
         Ok(())
@@ -258,16 +440,47 @@ impl<'a> RenderEngine<'a> {
         })
     }
 
+    pub fn generate_highlight_css(&mut self) -> crate::error::Result<()> {
+        log::debug!("Generate highlight css");
+        let ctx = get_context(snafu::location!())?;
+
+        let file_path = ctx
+            .config
+            .blog_path
+            .join(&ctx.config.deployment_folder)
+            .join("assets")
+            .join(HIGHLIGHT_CSS_FILE_NAME);
+        let css = format!("{}{}", Codeblock::highlight_info(&ctx).css()?, CODEBLOCK_CSS);
+        self.write(css, &file_path)
+    }
+
     pub fn generate_pages(&mut self) -> crate::error::Result<()> {
         log::debug!("Generate pages");
         let ctx = get_context(snafu::location!())?;
         for page in ctx.pages.items.iter() {
-            page.render(self, ())?;
+            for language in ctx.languages.iter() {
+                // Theme pages are rendered for every language
+                if page.lang.is_none() || page.lang.as_deref() == Some(language.code.as_str()) {
+                    page.render(self, language.clone())?;
+                }
+            }
         }
 
         Ok(())
     }
 
+    pub fn get_layout(&self, layout: Option<&str>, default: &str, document: &Path) -> crate::error::Result<String> {
+        let layout = layout.unwrap_or(default);
+        match self.env.get_template(layout) {
+            Err(error) if error.kind() == ErrorKind::TemplateNotFound => TemplateReferenceNotFoundSnafu { name: layout, document: document.display().to_string() }.fail(),
+            Err(error) => Err(error).context(LayoutTemplateFailedSnafu {
+                template: layout,
+                document: document.display().to_string(),
+            }),
+            Ok(_) => Ok(layout.to_string()),
+        }
+    }
+
     pub fn write(&self, content: String, path: &PathBuf) -> crate::error::Result<()> {
         let mut file = File::create(path).context(FileCreationFailedSnafu { path })?;
         file.write_all(content.as_bytes())
@@ -280,6 +493,12 @@ pub fn create_engine() -> crate::error::Result<RenderEngine<'static>> {
     engine.register_extension::<Codeblock>()?;
     engine.register_extension::<Quote>()?;
     engine.register_extension::<Gist>()?;
+    engine.register_extension::<IncludeCode>()?;
+    engine.register_extension::<Figure>()?;
+    engine.register_extension::<Gallery>()?;
+    engine.register_extension::<Search>()?;
+    engine.register_extension::<MetaTags>()?;
+    engine.register_extension::<StructuredData>()?;
     engine.register_extension::<AlertBox>()?;
     engine.register_extension::<Info>()?;
     engine.register_extension::<SocialMediaShare>()?;
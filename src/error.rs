@@ -16,11 +16,52 @@ pub enum Error {
         path: PathBuf,
     },
 
-    #[snafu(visibility(pub), display("Template reference found, path: {name}, at {loc}"))]
+    #[snafu(visibility(pub), display("Language not configured, lang: {lang}, at {loc}"))]
+    LanguageNotFound {
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        lang: String,
+    },
+
+    #[snafu(visibility(pub), display("Template reference not found, path: {name}, document: {document}, at {loc}"))]
     TemplateReferenceNotFound {
         #[snafu(implicit)]
         loc: snafu::Location,
         name: String,
+        document: String,
+    },
+
+    #[snafu(visibility(pub), display("Layout template failed, template: {template}, document: {document}, error: {source}, at {loc}"))]
+    LayoutTemplateFailed {
+        #[snafu(source)]
+        source: minijinja::Error,
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        template: String,
+        document: String,
+    },
+
+    #[snafu(visibility(pub), display("Rendered content not found, url: {url}, at {loc}"))]
+    RenderedContentNotFound {
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        url: String,
+    },
+
+    #[snafu(visibility(pub), display("Document reference not found, link: {reference}, document: {document}, at {loc}"))]
+    DocumentReferenceNotFound {
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        reference: String,
+        document: String,
+    },
+
+    #[snafu(visibility(pub), display("Broken links found, links: {count}, documents: {documents}, at {loc}"))]
+    BrokenLinksFound {
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        count: usize,
+        documents: usize,
     },
 
     #[snafu(visibility(pub), display("Context not initialized, at {loc}"))]
@@ -38,6 +79,12 @@ pub enum Error {
         loc: snafu::Location,
     },
 
+    #[snafu(visibility(pub), display("Preview requested but 'preview-secret' is not configured, at {loc}"))]
+    PreviewSecretNotConfigured {
+        #[snafu(implicit)]
+        loc: snafu::Location,
+    },
+
     #[snafu(visibility(pub), display("Failed to add tag, tag: {tag}, at {loc}"))]
     FailedToAddTag {
         #[snafu(implicit)]
@@ -103,6 +150,14 @@ pub enum Error {
         path: PathBuf,
     },
 
+    #[snafu(visibility(pub), display("Json serialization failed, at {loc}"))]
+    JsonSerializationFailed {
+        #[snafu(source)]
+        source: serde_json::Error,
+        #[snafu(implicit)]
+        loc: snafu::Location,
+    },
+
     #[snafu(visibility(pub), display("Yaml serializetion failed, at {loc}"))]
     YamlSerializationFailed {
         #[snafu(source)]
@@ -111,6 +166,59 @@ pub enum Error {
         loc: snafu::Location,
     },
 
+    #[snafu(visibility(pub), display("Math render failed, formula: {formula}, document: {document}, at {loc}"))]
+    MathRenderFailed {
+        #[snafu(source)]
+        source: Box<latex2mathml::LatexError>,
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        formula: String,
+        document: String,
+    },
+
+    #[snafu(visibility(pub), display("Image processing failed, path: {}, error: {source}, at {loc}", path.display()))]
+    ImageProcessingFailed {
+        #[snafu(source)]
+        source: image::ImageError,
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        path: PathBuf,
+    },
+
+    #[snafu(visibility(pub), display("Render hook failed, template: {template}, document: {document}, error: {source}, at {loc}"))]
+    RenderHookFailed {
+        #[snafu(source)]
+        source: minijinja::Error,
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        template: String,
+        document: String,
+    },
+
+    #[snafu(visibility(pub), display("Syntax highlighting failed, at {loc}"))]
+    HighlightFailed {
+        #[snafu(source)]
+        source: syntect::Error,
+        #[snafu(implicit)]
+        loc: snafu::Location,
+    },
+
+    #[snafu(visibility(pub), display("Syntax highlighting theme not found, theme: {theme}, at {loc}"))]
+    HighlightThemeNotFound {
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        theme: String,
+    },
+
+    #[snafu(visibility(pub), display("Invalid reference date, date: {date}, expected format: %Y-%m-%d %H:%M:%S, at {loc}"))]
+    InvalidReferenceDate {
+        #[snafu(source)]
+        source: chrono::ParseError,
+        #[snafu(implicit)]
+        loc: snafu::Location,
+        date: String,
+    },
+
     #[snafu(visibility(pub), display("Yaml deserializetion failed, content: {content}, at {loc}"))]
     YamlDeserializationFailed {
         #[snafu(source)]
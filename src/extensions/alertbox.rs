@@ -3,12 +3,16 @@ use std::sync::Arc;
 use minijinja::{
     args, render,
     value::{from_args, Kwargs, Object, ObjectRepr},
-    Error, ErrorKind, State, Value,
+    Error, ErrorKind, HtmlEscape, State, Value,
 };
+use pulldown_cmark::{BlockQuoteKind, Event, Tag, TagEnd};
 
-use crate::{context::get_context, tools::parse_yaml};
+use crate::{
+    context::{get_context, TimugContext},
+    render_hooks::{self, RenderHooks},
+};
 
-use super::Extension;
+use super::{render_block_markdown, Extension};
 
 static HTML: &str = include_str!("alertbox.html");
 
@@ -24,6 +28,130 @@ impl AlertBox {
     pub fn new() -> AlertBox {
         AlertBox {}
     }
+
+    pub fn render_html(ctx: &TimugContext, style: &str, title: &str, content: &str) -> String {
+        match ctx.get_template_page("alertbox.html") {
+            Some(page) => render!(page.content.as_str(), content => content, title => title, style => style),
+            None => render!(HTML, content => content, title => title, style => style),
+        }
+    }
+
+    // GitHub style "> [!NOTE]" blockquotes are rendered as alert boxes, "> [!NOTE] Title" sets a custom title
+    pub fn callout_events<'a>(events: Vec<Event<'a>>, lang: &str, hooks: &RenderHooks<'_>) -> crate::Result<Vec<Event<'a>>> {
+        let mut result = Vec::with_capacity(events.len());
+        let mut events = events.into_iter();
+
+        while let Some(event) = events.next() {
+            let kind = match &event {
+                Event::Start(Tag::BlockQuote(kind)) => *kind,
+                _ => {
+                    result.push(event);
+                    continue;
+                }
+            };
+
+            let mut inner = Vec::new();
+            let mut depth = 0;
+            for event in events.by_ref() {
+                match &event {
+                    Event::Start(Tag::BlockQuote(_)) => depth += 1,
+                    Event::End(TagEnd::BlockQuote(_)) if depth == 0 => break,
+                    Event::End(TagEnd::BlockQuote(_)) => depth -= 1,
+                    _ => (),
+                }
+                inner.push(event);
+            }
+
+            let (kind, title, inner) = match kind {
+                Some(kind) => (kind, None, inner),
+                None => match split_callout_title(inner) {
+                    Ok((kind, title, inner)) => (kind, Some(title), inner),
+                    Err(inner) => {
+                        result.push(event);
+                        result.extend(Self::callout_events(inner, lang, hooks)?);
+                        result.push(Event::End(TagEnd::BlockQuote(None)));
+                        continue;
+                    }
+                },
+            };
+
+            let ctx = get_context(snafu::location!())?;
+            let (style, title_key) = callout_style(kind);
+            let title = title
+                .filter(|title| !title.is_empty())
+                .unwrap_or_else(|| ctx.translate(lang, title_key));
+
+            let content = render_hooks::push_html(Self::callout_events(inner, lang, hooks)?, hooks)?;
+
+            let html = Self::render_html(&ctx, style, &HtmlEscape(&title).to_string(), &content);
+            result.push(Event::Html(html.into()));
+        }
+
+        Ok(result)
+    }
+}
+
+fn callout_style(kind: BlockQuoteKind) -> (&'static str, &'static str) {
+    match kind {
+        BlockQuoteKind::Note => ("info", "callout_note"),
+        BlockQuoteKind::Tip => ("success", "callout_tip"),
+        BlockQuoteKind::Important => ("info", "callout_important"),
+        BlockQuoteKind::Warning => ("warning", "callout_warning"),
+        BlockQuoteKind::Caution => ("error", "callout_caution"),
+    }
+}
+
+fn parse_callout_kind(kind: &str) -> Option<BlockQuoteKind> {
+    match kind.to_ascii_uppercase().as_str() {
+        "NOTE" => Some(BlockQuoteKind::Note),
+        "TIP" => Some(BlockQuoteKind::Tip),
+        "IMPORTANT" => Some(BlockQuoteKind::Important),
+        "WARNING" => Some(BlockQuoteKind::Warning),
+        "CAUTION" => Some(BlockQuoteKind::Caution),
+        _ => None,
+    }
+}
+
+// The parser only detects the callouts without a title, so the "[!KIND] Title" line of a plain blockquote is checked here
+fn split_callout_title(inner: Vec<Event<'_>>) -> Result<(BlockQuoteKind, String, Vec<Event<'_>>), Vec<Event<'_>>> {
+    if !matches!(inner.first(), Some(Event::Start(Tag::Paragraph))) {
+        return Err(inner);
+    }
+
+    let mut line = String::new();
+    let mut end = None;
+    for (index, event) in inner.iter().enumerate().skip(1) {
+        match event {
+            Event::Text(text) | Event::Code(text) => line.push_str(text),
+            Event::SoftBreak | Event::HardBreak | Event::End(TagEnd::Paragraph) => {
+                end = Some(index);
+                break;
+            }
+            _ => return Err(inner),
+        }
+    }
+
+    let parsed = end.and_then(|end| {
+        let (kind, title) = line.strip_prefix("[!")?.split_once(']')?;
+        Some((end, parse_callout_kind(kind)?, title.trim().to_string()))
+    });
+
+    let Some((end, kind, title)) = parsed else {
+        return Err(inner);
+    };
+
+    let mut rest = inner;
+    let ends_paragraph = matches!(rest[end], Event::End(TagEnd::Paragraph));
+    match ends_paragraph {
+        true => {
+            rest.drain(..=end);
+        }
+        false => {
+            rest.drain(1..=end);
+        }
+    }
+
+    Ok((kind, title, rest))
 }
 
 impl Object for AlertBox {
@@ -42,17 +170,8 @@ impl Object for AlertBox {
             .as_str()
             .ok_or_else(|| Error::new(ErrorKind::InvalidOperation, "call block did not return a string"))?;
 
-        let mut compiled_content = String::new();
-        pulldown_cmark::html::push_html(&mut compiled_content, parse_yaml(content));
-
-        let content = match ctx.get_template_page("alertbox.html") {
-            Some(page) => {
-                render!(page.content.as_str(), content => compiled_content, title => title, style => style)
-            }
-            None => render!(HTML, content => compiled_content, title => title, style => style),
-        };
-
-        Ok(Value::from_safe_string(content))
+        let compiled_content = render_block_markdown(state, &ctx, content)?;
+        Ok(Value::from_safe_string(Self::render_html(&ctx, style, title, &compiled_content)))
     }
 }
 
@@ -65,3 +184,51 @@ impl<'a> Extension<'a> for AlertBox {
         env.add_global(Self::name(), Value::from_object(Self::new()));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Options, Parser};
+
+    fn quote_events(content: &str) -> (Option<BlockQuoteKind>, Vec<Event<'_>>) {
+        let mut events: Vec<Event<'_>> = Parser::new_ext(content, Options::ENABLE_GFM).collect();
+        events.pop();
+        match events.remove(0) {
+            Event::Start(Tag::BlockQuote(kind)) => (kind, events),
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_callout_title() {
+        let (kind, _) = quote_events("> [!WARNING]\n> Be careful\n");
+        assert_eq!(kind, Some(BlockQuoteKind::Warning));
+        assert_eq!(callout_style(BlockQuoteKind::Warning), ("warning", "callout_warning"));
+        let translations = crate::i18n::Translations::load(&[]).unwrap();
+        assert_eq!(
+            translations
+                .translate(&["tr", "en"], "callout_warning", None, &[])
+                .as_deref(),
+            Some("Warning")
+        );
+
+        let (kind, inner) = quote_events("> [!tip] Pros & cons\n> Easy to use\n");
+        assert_eq!(kind, None);
+        let (kind, title, rest) = split_callout_title(inner).unwrap();
+        assert_eq!(kind, BlockQuoteKind::Tip);
+        assert_eq!(title, "Pros & cons");
+        assert_eq!(rest, vec![Event::Start(Tag::Paragraph), Event::Text("Easy to use".into()), Event::End(TagEnd::Paragraph)]);
+
+        let (_, inner) = quote_events("> [!CAUTION] Title only\n");
+        let (kind, title, rest) = split_callout_title(inner).unwrap();
+        assert_eq!(kind, BlockQuoteKind::Caution);
+        assert_eq!(title, "Title only");
+        assert!(rest.is_empty());
+
+        let (_, inner) = quote_events("> [link] text\n");
+        assert!(split_callout_title(inner).is_err());
+
+        let (_, inner) = quote_events("> [!UNKNOWN] text\n");
+        assert!(split_callout_title(inner).is_err());
+    }
+}
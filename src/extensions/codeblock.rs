@@ -1,19 +1,174 @@
-use std::{borrow::Cow, sync::Arc};
+use std::sync::Arc;
 
 use minijinja::{
-    args, render,
+    args, context, render,
     value::{from_args, Kwargs, Object, ObjectRepr},
-    Error, ErrorKind, State, Value,
+    Error, ErrorKind, HtmlEscape, State, Value,
 };
-use parking_lot::RwLockWriteGuard;
+use pulldown_cmark::{CodeBlockKind, Event, Tag, TagEnd};
 
-use crate::context::{get_context, TimugContext};
+use crate::{
+    context::{get_context, TimugContext},
+    highlight::HighlightInfo,
+};
+
+use super::{Extension, PageAssets};
+
+static HTML: &str = include_str!("codeblock.html");
+
+// Added only to the pages that have a copy button
+pub static COPY_SCRIPT: &str = r#"<script>
+document.querySelectorAll('.codeblock-copy').forEach((button) => {
+    button.addEventListener('click', () => {
+        navigator.clipboard.writeText(button.parentElement.querySelector('code').innerText);
+    });
+});
+</script>"#;
+
+// Line numbers, highlighted lines and the copy button styles are appended to the generated theme css
+pub static CSS: &str = r#"
+.codeblock { position: relative; }
+.codeblock-title { font-family: monospace; font-size: 0.875em; padding: 0.25em 0.75em; opacity: 0.8; }
+.codeblock-copy { position: absolute; top: 0.5em; right: 0.5em; font-size: 0.75em; padding: 0.125em 0.5em; opacity: 0.6; }
+.codeblock-copy:hover { opacity: 1; }
+.codeblock .line { display: inline-block; width: 100%; }
+.codeblock .line.highlighted { background-color: rgba(255, 255, 255, 0.1); box-shadow: inset 3px 0 0 rgba(255, 255, 255, 0.4); }
+.codeblock.linenos .line::before { content: attr(data-lineno); display: inline-block; width: 2.5em; margin-right: 1em; text-align: right; opacity: 0.5; user-select: none; }
+"#;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CodeOptions {
+    pub lang: String,
+    pub title: Option<String>,
+    pub lines: Vec<(usize, usize)>,
+    pub linenos: bool,
+    pub copy: bool,
+}
+
+impl CodeOptions {
+    // Fence info string, such as: rust title="main.rs" {3-5} linenos copy
+    pub fn parse(info: &str) -> Self {
+        let mut options = Self::default();
+
+        for (index, token) in tokenize(info).into_iter().enumerate() {
+            match token.split_once('=') {
+                Some(("title", value)) => options.title = Some(unquote(value).to_string()),
+                Some(("hl_lines", value)) => options.lines = parse_lines(unquote(value)),
+                Some(("linenos", value)) => options.linenos = unquote(value) == "true",
+                Some(("copy", value)) => options.copy = unquote(value) == "true",
+                Some(_) => (),
+                None if token.starts_with('{') => options.lines = parse_lines(token.trim_start_matches('{').trim_end_matches('}')),
+                None if token == "linenos" => options.linenos = true,
+                None if token == "copy" => options.copy = true,
+                None if index == 0 => options.lang = token,
+                None => (),
+            }
+        }
+
+        options
+    }
+
+    pub fn is_highlighted(&self, line: usize) -> bool {
+        self.lines
+            .iter()
+            .any(|(start, end)| *start <= line && line <= *end)
+    }
+}
+
+// Whitespace separated tokens, quoted values and braces are kept together
+fn tokenize(info: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut closing: Option<char> = None;
+
+    for char in info.chars() {
+        match (char, closing) {
+            (char, Some(close)) if char == close => {
+                closing = None;
+                token.push(char);
+            }
+            ('"', None) => {
+                closing = Some('"');
+                token.push(char);
+            }
+            ('{', None) => {
+                closing = Some('}');
+                token.push(char);
+            }
+            (char, None) if char.is_whitespace() => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            (char, _) => token.push(char),
+        }
+    }
+
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches('"')
+}
 
-use super::Extension;
+// Line ranges, such as: 1,3-5
+pub fn parse_lines(value: &str) -> Vec<(usize, usize)> {
+    value
+        .split([',', ' '])
+        .filter_map(|range| match range.trim().split_once('-') {
+            Some((start, end)) => Some((start.trim().parse().ok()?, end.trim().parse().ok()?)),
+            None => {
+                let line = range.trim().parse().ok()?;
+                Some((line, line))
+            }
+        })
+        .collect()
+}
+
+// Highlighted html is splitted into the lines, open spans are closed at the end of the line and reopened in the next line
+fn split_lines(html: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut open_spans: Vec<&str> = Vec::new();
+    let mut line = String::new();
+    let mut has_text = false;
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if rest.starts_with("<span") {
+            let end = rest.find('>').map(|index| index + 1).unwrap_or(rest.len());
+            open_spans.push(&rest[..end]);
+            line.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if rest.starts_with("</span>") {
+            open_spans.pop();
+            line.push_str("</span>");
+            rest = &rest["</span>".len()..];
+        } else if rest.starts_with('\n') {
+            line.push_str(&"</span>".repeat(open_spans.len()));
+            lines.push(std::mem::take(&mut line));
+            line.push_str(&open_spans.concat());
+            has_text = false;
+            rest = &rest[1..];
+        } else {
+            let end = rest.find(['<', '\n']).unwrap_or(rest.len()).max(1);
+            line.push_str(&rest[..end]);
+            has_text = true;
+            rest = &rest[end..];
+        }
+    }
 
-static HTML: &str = "<pre>
-  <code class=\"language-{{lang}}\">{{ content | safe }}</code>
-</pre>";
+    // Closing spans after the last new line are not a line
+    if has_text {
+        line.push_str(&"</span>".repeat(open_spans.len()));
+        lines.push(line);
+    }
+
+    lines
+}
 
 pub struct Codeblock;
 
@@ -27,6 +182,68 @@ impl Codeblock {
     pub fn new() -> Codeblock {
         Codeblock {}
     }
+
+    pub fn highlight_info(ctx: &TimugContext) -> HighlightInfo {
+        ctx.get_config::<HighlightInfo>(Self::name())
+            .unwrap_or_default()
+    }
+
+    pub fn render_html(ctx: &TimugContext, content: &str, options: &CodeOptions, lang: &str) -> crate::Result<String> {
+        let info = Self::highlight_info(ctx);
+        let copy_label = HtmlEscape(&ctx.translate(lang, "copy")).to_string();
+        let content = info.highlight(content, &options.lang)?;
+        let style = info.background()?;
+        let lines: Vec<Value> = split_lines(&content)
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| context! { number => index + 1, content => line, highlighted => options.is_highlighted(index + 1) })
+            .collect();
+
+        Ok(match ctx.get_template_page("codeblock.html") {
+            Some(page) => render!(page.content.as_str(), content => content, lines => lines, lang => options.lang, title => options.title, linenos => options.linenos, copy => options.copy, copy_label => copy_label, style => style),
+            None => render!(HTML, content => content, lines => lines, lang => options.lang, title => options.title, linenos => options.linenos, copy => options.copy, copy_label => copy_label, style => style),
+        })
+    }
+
+    // Code block of a template call, the copy script is added to the page when the block has a copy button
+    pub fn render_in_state(state: &State<'_, '_>, ctx: &TimugContext, content: &str, options: &CodeOptions) -> Result<String, Error> {
+        let lang = state.lookup("lang").unwrap_or_default();
+        let content = Self::render_html(ctx, content, options, lang.as_str().unwrap_or_default()).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+        if options.copy {
+            PageAssets::add_after_body(state, COPY_SCRIPT);
+        }
+        Ok(content)
+    }
+
+    // Fenced and indented markdown code blocks are highlighted with the same markup, the copy script is added to the page assets
+    pub fn highlight_events<'a>(events: impl Iterator<Item = Event<'a>>, lang: &str, after_bodies: &Value) -> crate::Result<Vec<Event<'a>>> {
+        let ctx = get_context(snafu::location!())?;
+        let mut result = Vec::new();
+        let mut code_block: Option<(CodeOptions, String)> = None;
+
+        for event in events {
+            match (event, &mut code_block) {
+                (Event::Start(Tag::CodeBlock(kind)), _) => {
+                    let options = match kind {
+                        CodeBlockKind::Fenced(info) => CodeOptions::parse(&info),
+                        CodeBlockKind::Indented => CodeOptions::default(),
+                    };
+                    code_block = Some((options, String::new()));
+                }
+                (Event::Text(text), Some((_, content))) => content.push_str(&text),
+                (Event::End(TagEnd::CodeBlock), Some((options, content))) => {
+                    result.push(Event::Html(Self::render_html(&ctx, content, options, lang)?.into()));
+                    if options.copy {
+                        PageAssets::add(after_bodies, COPY_SCRIPT);
+                    }
+                    code_block = None;
+                }
+                (event, _) => result.push(event),
+            }
+        }
+
+        Ok(result)
+    }
 }
 
 impl Object for Codeblock {
@@ -46,11 +263,15 @@ impl Object for Codeblock {
             .ok_or_else(|| Error::new(ErrorKind::InvalidOperation, "call block did not return a string"))?
             .trim();
 
-        let content = match ctx.get_template_page("codeblock.html") {
-            Some(page) => render!(page.content.as_str(), content => content, lang => lang),
-            None => render!(HTML, content => content, lang => lang),
+        let options = CodeOptions {
+            lang: lang.to_string(),
+            title: kwargs.get("title")?,
+            lines: parse_lines(kwargs.get::<Option<&str>>("hl_lines")?.unwrap_or_default()),
+            linenos: kwargs.get::<Option<bool>>("linenos")?.unwrap_or_default(),
+            copy: kwargs.get::<Option<bool>>("copy")?.unwrap_or_default(),
         };
 
+        let content = Self::render_in_state(state, &ctx, content, &options)?;
         Ok(Value::from_safe_string(content))
     }
 }
@@ -65,20 +286,36 @@ impl<'a> Extension<'a> for Codeblock {
     }
 
     fn header() -> &'static str {
-        r#"<link rel="stylesheet" href="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/styles/default.min.css">
-<script src="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/highlight.min.js"></script>
-<link rel="stylesheet" href="https://unpkg.com/@highlightjs/cdn-assets@11.9.0/styles/atom-one-dark.min.css" />"#
+        r#"<link rel="stylesheet" href="/assets/highlight.css">"#
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_options() {
+        let options = CodeOptions::parse(r#"rust title="main file.rs" {1,3-5} linenos copy"#);
+        assert_eq!(options.lang, "rust");
+        assert_eq!(options.title.as_deref(), Some("main file.rs"));
+        assert_eq!(options.lines, vec![(1, 1), (3, 5)]);
+        assert!(options.linenos);
+        assert!(options.copy);
+        assert!(options.is_highlighted(4));
+        assert!(!options.is_highlighted(2));
+
+        let options = CodeOptions::parse(r#"python hl_lines="2" linenos=false"#);
+        assert_eq!(options.lang, "python");
+        assert_eq!(options.lines, vec![(2, 2)]);
+        assert!(!options.linenos);
+        assert_eq!(CodeOptions::parse(""), CodeOptions::default());
     }
 
-    fn after_body(ctx: &'_ mut RwLockWriteGuard<'static, TimugContext>) {
-        ctx.after_bodies.push(Cow::Borrowed(
-            r#"<script>
-document.addEventListener('DOMContentLoaded', (event) => {
-    document.querySelectorAll('pre code').forEach((block) => {
-        hljs.highlightBlock(block);
-    });
-});
-</script>"#,
-        ))
+    #[test]
+    fn test_split_lines() {
+        let lines = split_lines("<span class=\"a\">x\n<span class=\"b\">y</span>\n</span>");
+        assert_eq!(lines, vec!["<span class=\"a\">x</span>", "<span class=\"a\"><span class=\"b\">y</span></span>"]);
+        assert_eq!(split_lines("a &lt; b\nc"), vec!["a &lt; b", "c"]);
     }
 }
@@ -5,7 +5,6 @@ use minijinja::{
     value::{Object, ObjectRepr},
     Error, ErrorKind, State, Value,
 };
-use serde::{Deserialize, Serialize};
 
 use crate::context::get_context;
 
@@ -13,13 +12,6 @@ use super::Extension;
 
 static HTML: &str = include_str!("contacts.html");
 
-#[derive(Debug, Default, Serialize, Deserialize)]
-struct ContactInfo {
-    icon: String,
-    link: String,
-    description: Option<String>,
-}
-
 pub struct Contacts;
 
 impl std::fmt::Debug for Contacts {
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use minijinja::{
+    args, render,
+    value::{from_args, Kwargs, Object, ObjectRepr},
+    Error, ErrorKind, HtmlEscape, State, Value,
+};
+use pulldown_cmark::{CowStr, Event, LinkType, Tag, TagEnd};
+
+use crate::{
+    context::get_context,
+    render_hooks::{self, escape_url, plain_text, RenderHooks},
+};
+
+use super::{render_block_markdown, Extension};
+
+static HTML: &str = include_str!("figure.html");
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FigureInfo {
+    pub src: String,
+    pub alt: String,
+    pub caption: String,
+    pub link: Option<String>,
+    pub credit: Option<String>,
+}
+
+pub struct Figure;
+
+impl std::fmt::Debug for Figure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "figure")
+    }
+}
+
+impl Figure {
+    pub fn new() -> Figure {
+        Figure {}
+    }
+
+    // Image is rendered like a markdown image, so the render hooks and the responsive images are applied to it
+    pub fn render_html(info: &FigureInfo, hooks: &RenderHooks<'_>) -> crate::Result<String> {
+        let mut events = vec![Event::Start(Tag::Image {
+            link_type: LinkType::Inline,
+            dest_url: CowStr::from(info.src.as_str()),
+            title: CowStr::from(""),
+            id: CowStr::from(""),
+        })];
+        if !info.alt.is_empty() {
+            events.push(Event::Text(CowStr::from(info.alt.as_str())));
+        }
+        events.push(Event::End(TagEnd::Image));
+
+        let image = render_hooks::push_html(events, hooks)?;
+        let link = info.link.as_deref().map(escape_url);
+        let alt = HtmlEscape(&info.alt).to_string();
+        let credit = info
+            .credit
+            .as_deref()
+            .map(|credit| HtmlEscape(credit).to_string());
+
+        let ctx = get_context(snafu::location!())?;
+        Ok(match ctx.get_template_page("figure.html") {
+            Some(page) => render!(page.content.as_str(), image => image, src => escape_url(&info.src), alt => alt, caption => info.caption, link => link, credit => credit),
+            None => render!(HTML, image => image, src => escape_url(&info.src), alt => alt, caption => info.caption, link => link, credit => credit),
+        })
+    }
+
+    // Paragraphs that only have an image with a title, optionally wrapped with a link, are rendered as figures
+    pub fn figure_events<'a>(events: Vec<Event<'a>>, hooks: &RenderHooks<'_>) -> crate::Result<Vec<Event<'a>>> {
+        let mut result = Vec::with_capacity(events.len());
+        let mut index = 0;
+
+        while index < events.len() {
+            if events[index] == Event::Start(Tag::Paragraph) {
+                if let Some(end) = events[index..]
+                    .iter()
+                    .position(|event| *event == Event::End(TagEnd::Paragraph))
+                {
+                    if let Some(info) = parse_figure(&events[index + 1..index + end]) {
+                        result.push(Event::Html(Self::render_html(&info, hooks)?.into()));
+                        index += end + 1;
+                        continue;
+                    }
+                }
+            }
+
+            result.push(events[index].clone());
+            index += 1;
+        }
+
+        Ok(result)
+    }
+}
+
+fn parse_figure(events: &[Event<'_>]) -> Option<FigureInfo> {
+    let (link, events) = match (events.first(), events.last()) {
+        (Some(Event::Start(Tag::Link { dest_url, .. })), Some(Event::End(TagEnd::Link))) => (Some(dest_url.to_string()), &events[1..events.len() - 1]),
+        _ => (None, events),
+    };
+
+    match (events.first(), events.last()) {
+        (Some(Event::Start(Tag::Image { dest_url, title, .. })), Some(Event::End(TagEnd::Image))) if !title.is_empty() => {
+            let inner = &events[1..events.len() - 1];
+            if inner
+                .iter()
+                .any(|event| matches!(event, Event::Start(Tag::Image { .. })))
+            {
+                return None;
+            }
+
+            Some(FigureInfo {
+                src: dest_url.to_string(),
+                alt: plain_text(inner),
+                caption: HtmlEscape(title).to_string(),
+                link,
+                credit: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+impl Object for Figure {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(self: &Arc<Self>, state: &State<'_, '_>, args: &[Value]) -> Result<Value, Error> {
+        let (src, kwargs): (&str, Kwargs) = from_args(args)?;
+        let ctx = get_context(snafu::location!()).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+
+        // Content of the call block is used as a markdown caption when the caption argument is missing
+        let caption = match kwargs.get::<Option<String>>("caption")? {
+            Some(caption) => caption,
+            None => match kwargs.get::<Option<Value>>("caller")? {
+                Some(caller) => {
+                    let content = caller.call(state, args!())?;
+                    let content = content.as_str().unwrap_or_default().trim();
+                    match content.is_empty() {
+                        true => String::new(),
+                        false => render_block_markdown(state, &ctx, content)?,
+                    }
+                }
+                None => String::new(),
+            },
+        };
+        drop(ctx);
+
+        let info = FigureInfo {
+            src: src.to_string(),
+            alt: kwargs.get::<Option<String>>("alt")?.unwrap_or_default(),
+            caption,
+            link: kwargs.get("link")?,
+            credit: kwargs.get("credit")?,
+        };
+
+        let content = RenderHooks::load(state.name())
+            .and_then(|hooks| Figure::render_html(&info, &hooks))
+            .map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+        Ok(Value::from_safe_string(content))
+    }
+}
+
+impl<'a> Extension<'a> for Figure {
+    fn name() -> &'static str {
+        "figure"
+    }
+
+    fn register(env: &mut minijinja::Environment<'a>) {
+        env.add_global(Self::name(), Value::from_object(Self::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Options, Parser};
+
+    fn paragraph(content: &str) -> Vec<Event<'_>> {
+        let mut events: Vec<_> = Parser::new_ext(content, Options::empty()).collect();
+        events.remove(0);
+        events.pop();
+        events
+    }
+
+    #[test]
+    fn test_parse_figure() {
+        assert_eq!(
+            parse_figure(&paragraph("![A &lt;cat&gt;](/assets/cat.png \"Sleeping & happy\")")),
+            Some(FigureInfo {
+                src: "/assets/cat.png".to_string(),
+                alt: "A <cat>".to_string(),
+                caption: "Sleeping &amp; happy".to_string(),
+                link: None,
+                credit: None,
+            })
+        );
+
+        let info = parse_figure(&paragraph("[![Cat](/assets/cat.png \"Cat\")](https://example.com)")).unwrap();
+        assert_eq!(info.link.as_deref(), Some("https://example.com"));
+
+        assert_eq!(parse_figure(&paragraph("![Cat](/assets/cat.png)")), None);
+        assert_eq!(parse_figure(&paragraph("Look ![Cat](/assets/cat.png \"Cat\")")), None);
+    }
+}
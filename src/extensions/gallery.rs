@@ -0,0 +1,211 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use minijinja::{
+    context, render,
+    value::{from_args, Kwargs, Object, ObjectRepr},
+    Error, ErrorKind, HtmlEscape, State, Value,
+};
+use snafu::ResultExt;
+
+use crate::{
+    context::get_context,
+    error::{ReadingDirectoryFailedSnafu, YamlDeserializationFailedSnafu},
+    images::{ImageProcessor, Thumbnail},
+    tools::get_file_content,
+};
+
+use super::{source_path, Extension, PageAssets};
+
+static HTML: &str = include_str!("gallery.html");
+
+// Optional captions of the images in the gallery folder, keys are the file names
+pub const CAPTIONS_FILE_NAME: &str = "gallery.yaml";
+
+const DEFAULT_THUMBNAIL_WIDTH: u32 = 320;
+
+static LIGHTBOX: &str = r#"<script>
+(() => {
+    const links = Array.from(document.querySelectorAll('.gallery a'));
+    if (links.length === 0) return;
+
+    const overlay = document.createElement('div');
+    overlay.style.cssText = 'position:fixed;inset:0;display:none;flex-direction:column;align-items:center;justify-content:center;background:rgba(0,0,0,.9);z-index:1000;cursor:zoom-out';
+    overlay.innerHTML = '<img style="max-width:95vw;max-height:85vh"><p style="color:#fff;margin-top:.75rem"></p>';
+    document.body.appendChild(overlay);
+
+    let current = 0;
+    const show = (index) => {
+        current = (index + links.length) % links.length;
+        overlay.querySelector('img').src = links[current].href;
+        overlay.querySelector('img').alt = links[current].querySelector('img').alt;
+        overlay.querySelector('p').textContent = links[current].dataset.caption || '';
+        overlay.style.display = 'flex';
+    };
+
+    links.forEach((link, index) => link.addEventListener('click', (event) => {
+        event.preventDefault();
+        show(index);
+    }));
+    overlay.addEventListener('click', () => overlay.style.display = 'none');
+    document.addEventListener('keydown', (event) => {
+        if (overlay.style.display === 'none') return;
+        if (event.key === 'Escape') overlay.style.display = 'none';
+        if (event.key === 'ArrowRight') show(current + 1);
+        if (event.key === 'ArrowLeft') show(current - 1);
+    });
+})();
+</script>"#;
+
+pub struct Gallery;
+
+impl std::fmt::Debug for Gallery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "gallery")
+    }
+}
+
+impl Gallery {
+    pub fn new() -> Gallery {
+        Gallery {}
+    }
+}
+
+fn load_captions(folder: &Path) -> crate::Result<HashMap<String, String>> {
+    let path = folder.join(CAPTIONS_FILE_NAME);
+    if !path.is_file() {
+        return Ok(HashMap::new());
+    }
+
+    let content = get_file_content(&path)?;
+    serde_yaml::from_str(&content).context(YamlDeserializationFailedSnafu { content })
+}
+
+// Images of the folder sorted by their file names, other files are ignored
+fn images(folder: &Path, processor: &ImageProcessor, width: u32) -> crate::Result<Vec<Value>> {
+    let captions = load_captions(folder)?;
+    let mut files: Vec<_> = std::fs::read_dir(folder)
+        .context(ReadingDirectoryFailedSnafu { path: folder })?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+
+    let mut images = Vec::new();
+    for file in files.iter() {
+        let Some(Thumbnail { thumbnail, original }) = processor.thumbnail(file, width)? else {
+            continue;
+        };
+
+        let file_name = file
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let caption = captions.get(&file_name).cloned().unwrap_or_default();
+        let alt = match caption.is_empty() {
+            true => file
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .replace(['-', '_'], " "),
+            false => caption.clone(),
+        };
+
+        images.push(context! {
+            url => original.url,
+            thumbnail => thumbnail.url,
+            width => thumbnail.width,
+            height => thumbnail.height,
+            caption => HtmlEscape(&caption).to_string(),
+            alt => HtmlEscape(&alt).to_string(),
+            name => file_name,
+        });
+    }
+
+    Ok(images)
+}
+
+impl Object for Gallery {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(self: &Arc<Self>, state: &State<'_, '_>, args: &[Value]) -> Result<Value, Error> {
+        let (path, kwargs): (&str, Kwargs) = from_args(args)?;
+        let ctx = get_context(snafu::location!()).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+
+        let width = kwargs
+            .get::<Option<u32>>("width")?
+            .unwrap_or(DEFAULT_THUMBNAIL_WIDTH);
+        let processor = ImageProcessor::with_config(&ctx, ctx.config.images.clone().unwrap_or_default());
+        let folder = source_path(&ctx.config.blog_path, path)?;
+        let images = images(&folder, &processor, width).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+
+        PageAssets::add_after_body(state, LIGHTBOX);
+        let env = state.env();
+        let content = match ctx.get_template_page("gallery.html") {
+            Some(page) => render!(in env, page.content.as_str(), images => images),
+            None => render!(in env, HTML, images => images),
+        };
+
+        Ok(Value::from_safe_string(content))
+    }
+}
+
+impl<'a> Extension<'a> for Gallery {
+    fn name() -> &'static str {
+        "gallery"
+    }
+
+    fn register(env: &mut minijinja::Environment<'a>) {
+        env.add_global(Self::name(), Value::from_object(Self::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::TimugContext;
+    use image::{Rgb, RgbImage};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_images() {
+        let dir = tempdir().unwrap();
+        let folder = dir.path().join("trip");
+        std::fs::create_dir_all(&folder).unwrap();
+        RgbImage::from_pixel(800, 400, Rgb([10, 20, 30]))
+            .save(folder.join("b-beach.png"))
+            .unwrap();
+        RgbImage::from_pixel(400, 400, Rgb([30, 20, 10]))
+            .save(folder.join("a-hill.jpg"))
+            .unwrap();
+        std::fs::write(folder.join("notes.txt"), "not an image").unwrap();
+        std::fs::write(folder.join(CAPTIONS_FILE_NAME), "b-beach.png: Sunny <beach>").unwrap();
+
+        let mut ctx = TimugContext {
+            statics_path: dir.path().join("assets"),
+            cache_path: dir.path().join("cache"),
+            ..Default::default()
+        };
+        ctx.config.deployment_folder = dir.path().join("public");
+        let processor = ImageProcessor::with_config(&ctx, Default::default());
+
+        let images = images(&folder, &processor, 200).unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].get_attr("alt").unwrap().as_str(), Some("a hill"));
+        assert_eq!(images[0].get_attr("caption").unwrap().as_str(), Some(""));
+        assert_eq!(images[1].get_attr("caption").unwrap().as_str(), Some("Sunny &lt;beach&gt;"));
+        assert_eq!(images[1].get_attr("width").unwrap(), Value::from(200));
+        assert_eq!(images[1].get_attr("height").unwrap(), Value::from(100));
+        let url = images[1].get_attr("url").unwrap();
+        let url = url.as_str().unwrap();
+        assert!(url.starts_with("/assets/resized/b-beach-") && !url.ends_with("-800.png"));
+        assert!(dir
+            .path()
+            .join("public")
+            .join(url.trim_start_matches('/'))
+            .is_file());
+    }
+}
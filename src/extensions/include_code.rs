@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use minijinja::{
+    value::{from_args, Kwargs, Object, ObjectRepr},
+    Error, ErrorKind, State, Value,
+};
+
+use crate::{context::get_context, tools::get_file_content};
+
+use super::{
+    codeblock::{parse_lines, CodeOptions, Codeblock},
+    source_path, Extension,
+};
+
+pub struct IncludeCode;
+
+impl std::fmt::Debug for IncludeCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "include_code")
+    }
+}
+
+impl IncludeCode {
+    pub fn new() -> IncludeCode {
+        IncludeCode {}
+    }
+}
+
+// Comment syntaxes of the region markers, like "// region: main" or "<!-- endregion: main -->"
+const COMMENT_STARTS: [&str; 6] = ["//", "#", "--", ";", "/*", "<!--"];
+const COMMENT_ENDS: [&str; 2] = ["*/", "-->"];
+
+#[derive(Debug, PartialEq)]
+enum Marker<'a> {
+    Start(&'a str),
+    End(&'a str),
+}
+
+// Only a comment line that is exactly "region: <name>" or "endregion: <name>" is a marker, other lines are code
+fn marker(line: &str) -> Option<Marker<'_>> {
+    let line = line.trim();
+    let text = COMMENT_STARTS
+        .iter()
+        .find_map(|start| line.strip_prefix(start))?;
+    let text = COMMENT_ENDS
+        .iter()
+        .fold(text, |text, end| text.strip_suffix(end).unwrap_or(text))
+        .trim();
+
+    let (end, name) = match text.strip_prefix("endregion: ") {
+        Some(name) => (true, name.trim()),
+        None => (false, text.strip_prefix("region: ")?.trim()),
+    };
+
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+
+    Some(match end {
+        true => Marker::End(name),
+        false => Marker::Start(name),
+    })
+}
+
+// Lines between the "region: <name>" and "endregion: <name>" markers, nested markers are removed
+fn select_region(content: &str, region: &str) -> Option<String> {
+    let mut lines = content
+        .lines()
+        .skip_while(|line| marker(line) != Some(Marker::Start(region)));
+    lines.next()?;
+
+    let lines: Vec<&str> = lines
+        .take_while(|line| marker(line) != Some(Marker::End(region)))
+        .filter(|line| marker(line).is_none())
+        .collect();
+    Some(lines.join("\n"))
+}
+
+// Line ranges are 1-based and applied to the whole file, region markers are removed like in the region mode
+fn select_lines(content: &str, lines: &str) -> String {
+    let ranges = parse_lines(lines);
+    content
+        .lines()
+        .zip(1..)
+        .filter(|(line, number)| {
+            marker(line).is_none()
+                && ranges
+                    .iter()
+                    .any(|(start, end)| start <= number && number <= end)
+        })
+        .map(|(line, _)| line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Object for IncludeCode {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(self: &Arc<Self>, state: &State<'_, '_>, args: &[Value]) -> Result<Value, Error> {
+        let (path, kwargs): (&str, Kwargs) = from_args(args)?;
+        let ctx = get_context(snafu::location!()).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+
+        let file_path = source_path(&ctx.config.blog_path, path)?;
+        let content = get_file_content(&file_path).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+
+        let region: Option<&str> = kwargs.get("region")?;
+        let lines: Option<&str> = kwargs.get("lines")?;
+        let content = match (region, lines) {
+            (Some(region), _) => select_region(&content, region).ok_or_else(|| Error::new(ErrorKind::InvalidOperation, format!("Region '{}' not found in {}", region, file_path.display())))?,
+            (None, Some(lines)) => select_lines(&content, lines),
+            (None, None) => content
+                .lines()
+                .filter(|line| marker(line).is_none())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+
+        let lang = match kwargs.get::<Option<String>>("lang")? {
+            Some(lang) => lang,
+            None => file_path
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+
+        let options = CodeOptions {
+            lang,
+            title: kwargs.get("title")?,
+            lines: parse_lines(kwargs.get::<Option<&str>>("hl_lines")?.unwrap_or_default()),
+            linenos: kwargs.get::<Option<bool>>("linenos")?.unwrap_or_default(),
+            copy: kwargs.get::<Option<bool>>("copy")?.unwrap_or_default(),
+        };
+
+        let content = Codeblock::render_in_state(state, &ctx, &content, &options)?;
+        Ok(Value::from_safe_string(content))
+    }
+}
+
+impl<'a> Extension<'a> for IncludeCode {
+    fn name() -> &'static str {
+        "include_code"
+    }
+
+    fn register(env: &mut minijinja::Environment<'a>) {
+        env.add_global(Self::name(), Value::from_object(Self::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CONTENT: &str = "use std::io;\n\n// region: main\nfn main() {\n    // region: body\n    println!(\"hello\");\n    // endregion: body\n}\n// endregion: main\n";
+
+    #[test]
+    fn test_select_region() {
+        assert_eq!(select_region(CONTENT, "main").as_deref(), Some("fn main() {\n    println!(\"hello\");\n}"));
+        assert_eq!(select_region(CONTENT, "body").as_deref(), Some("    println!(\"hello\");"));
+        assert_eq!(select_region(CONTENT, "missing"), None);
+    }
+
+    #[test]
+    fn test_select_lines() {
+        assert_eq!(select_lines(CONTENT, "1,4"), "use std::io;\nfn main() {");
+        assert_eq!(select_lines(CONTENT, "8-20"), "}");
+    }
+
+    #[test]
+    fn test_marker() {
+        let content = "fn main() {\n    // region: main\n    let text = \"region: main\";\n    // this region: main is not a marker\n    /* endregion: main */\n}";
+        assert_eq!(select_region(content, "main").as_deref(), Some("    let text = \"region: main\";\n    // this region: main is not a marker"));
+        assert_eq!(select_lines(content, "2-3"), "    let text = \"region: main\";");
+        assert_eq!(marker("<!-- endregion: head -->"), Some(Marker::End("head")));
+        assert_eq!(marker("# region: setup"), Some(Marker::Start("setup")));
+        assert_eq!(marker("// region: two words"), None);
+    }
+}
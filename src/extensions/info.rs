@@ -6,9 +6,9 @@ use minijinja::{
     Error, ErrorKind, State, Value,
 };
 
-use crate::{context::get_context, tools::parse_yaml};
+use crate::context::get_context;
 
-use super::Extension;
+use super::{render_block_markdown, Extension};
 
 static HTML: &str = include_str!("info.html");
 
@@ -42,8 +42,7 @@ impl Object for Info {
             .as_str()
             .ok_or_else(|| Error::new(ErrorKind::InvalidOperation, "call block did not return a string"))?;
 
-        let mut compiled_content = String::new();
-        pulldown_cmark::html::push_html(&mut compiled_content, parse_yaml(content));
+        let compiled_content = render_block_markdown(state, &ctx, content)?;
 
         let content = match ctx.get_template_page("info.html") {
             Some(page) => render!(page.content.as_str(), content => compiled_content),
@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use minijinja::{
+    render,
+    value::{from_args, Kwargs, Object, ObjectRepr},
+    Environment, Error, ErrorKind, HtmlEscape, State, Value,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{context::get_context, feed::absolute_url, page::Page, post::Post, references, render_hooks::escape_url};
+
+use super::Extension;
+
+static HTML: &str = include_str!("meta_tags.html");
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct SeoInfo {
+    // Default share image of the documents without a cover
+    pub image: Option<String>,
+
+    // Twitter handle of the site, like @timug
+    pub twitter: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct MetaTagsInfo {
+    pub site_name: String,
+    pub title: String,
+    pub description: String,
+    pub url: Option<String>,
+    pub image: Option<String>,
+    pub published_time: Option<String>,
+    pub tags: Vec<String>,
+    pub twitter: Option<String>,
+}
+
+impl MetaTagsInfo {
+    pub fn render_html(&self, env: &Environment<'_>, template: &str) -> String {
+        let escape = |value: &str| HtmlEscape(value).to_string();
+        let kind = match self.published_time {
+            Some(_) => "article",
+            None => "website",
+        };
+
+        render!(in env, template,
+            site_name => escape(&self.site_name),
+            title => escape(&self.title),
+            description => escape(&self.description),
+            url => self.url.as_deref().map(escape_url),
+            image => self.image.as_deref().map(escape_url),
+            type => kind,
+            published_time => self.published_time,
+            tags => self.tags.iter().map(|tag| escape(tag)).collect::<Vec<_>>(),
+            twitter => self.twitter.as_deref().map(escape),
+        )
+    }
+}
+
+// Relative links are prefixed with the site url, social networks only accept absolute links
+pub fn absolute_link(site_url: &str, link: &str) -> String {
+    match link.contains("://") || link.starts_with("//") {
+        true => link.to_string(),
+        false => absolute_url(site_url, &format!("/{}", link.trim_start_matches('/'))),
+    }
+}
+
+pub struct MetaTags;
+
+impl std::fmt::Debug for MetaTags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "meta_tags")
+    }
+}
+
+impl MetaTags {
+    pub fn new() -> MetaTags {
+        MetaTags {}
+    }
+}
+
+impl Object for MetaTags {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(self: &Arc<Self>, state: &State<'_, '_>, args: &[Value]) -> Result<Value, Error> {
+        let (kwargs,): (Kwargs,) = from_args(args)?;
+        let ctx = get_context(snafu::location!()).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+        let seo = ctx.get_config::<SeoInfo>("seo").unwrap_or_default();
+
+        // Posts and custom pages are documents, theme pages only have the site values
+        let data = state.lookup("data").unwrap_or_default();
+        let post = data.downcast_object_ref::<Post>();
+        let page = data.downcast_object_ref::<Page>();
+        let is_document = post.is_some() || page.is_some_and(|page| page.lang.is_some());
+        let attr = |key: &str| -> Option<String> {
+            data.get_attr(key)
+                .ok()
+                .filter(|value| !value.is_undefined() && !value.is_none())
+                .map(|value| value.to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let url = match page {
+            Some(page) if page.lang.is_none() => {
+                let lang = state.lookup("lang").unwrap_or_default();
+                Some(references::page_url(&ctx, page, lang.as_str().unwrap_or_default()))
+            }
+            _ => attr("url"),
+        };
+
+        // Tag and author pages have no document, their output url is used
+        let url = url.or_else(|| {
+            state
+                .lookup("current_url")
+                .and_then(|url| url.as_str().map(str::to_string))
+        });
+
+        let meta_tags = MetaTagsInfo {
+            site_name: ctx.config.title.clone(),
+            title: kwargs
+                .get::<Option<String>>("title")?
+                .or_else(|| is_document.then(|| attr("title")).flatten())
+                .unwrap_or_else(|| ctx.config.title.clone()),
+            description: kwargs
+                .get::<Option<String>>("description")?
+                .or_else(|| attr("description"))
+                .unwrap_or_else(|| ctx.config.description.clone()),
+            url: kwargs
+                .get::<Option<String>>("url")?
+                .or_else(|| attr("canonical"))
+                .or(url)
+                .map(|url| absolute_link(&ctx.config.site_url, &url)),
+            image: kwargs
+                .get::<Option<String>>("image")?
+                .or_else(|| attr("cover"))
+                .or(seo.image)
+                .map(|image| absolute_link(&ctx.config.site_url, &image)),
+            published_time: post.map(|post| post.date().to_rfc3339()),
+            tags: post.map(|post| post.tags()).unwrap_or_default(),
+            twitter: seo.twitter,
+        };
+
+        let env = state.env();
+        let content = match ctx.get_template_page("meta_tags.html") {
+            Some(page) => meta_tags.render_html(env, page.content.as_str()),
+            None => meta_tags.render_html(env, HTML),
+        };
+
+        Ok(Value::from_safe_string(content))
+    }
+}
+
+impl<'a> Extension<'a> for MetaTags {
+    fn name() -> &'static str {
+        "meta_tags"
+    }
+
+    fn register(env: &mut minijinja::Environment<'a>) {
+        env.add_global(Self::name(), Value::from_object(Self::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html() {
+        let meta_tags = MetaTagsInfo {
+            site_name: "Blog".to_string(),
+            title: "Hello \"World\"".to_string(),
+            description: "A <short> post".to_string(),
+            url: Some(absolute_link("https://example.com/", "/2024/1/2/hello.html")),
+            image: Some(absolute_link("https://example.com", "assets/cover.png")),
+            published_time: Some("2024-01-02T10:00:00+00:00".to_string()),
+            tags: vec!["rust".to_string()],
+            twitter: None,
+        };
+
+        let html = meta_tags.render_html(&Environment::new(), HTML);
+        assert!(html.contains(r#"<link rel="canonical" href="https://example.com/2024/1/2/hello.html">"#));
+        assert!(html.contains(r#"<meta property="og:title" content="Hello &quot;World&quot;">"#));
+        assert!(html.contains(r#"<meta name="description" content="A &lt;short&gt; post">"#));
+        assert!(html.contains(r#"<meta property="og:image" content="https://example.com/assets/cover.png">"#));
+        assert!(html.contains(r#"<meta property="og:type" content="article">"#));
+        assert!(html.contains(r#"<meta property="article:tag" content="rust">"#));
+        assert!(html.contains(r#"<meta name="twitter:card" content="summary_large_image">"#));
+        assert!(!html.contains("twitter:site"));
+
+        assert_eq!(absolute_link("https://example.com", "https://cdn.example.com/a.png"), "https://cdn.example.com/a.png");
+    }
+}
@@ -1,19 +1,35 @@
-use minijinja::Environment;
-use parking_lot::RwLockWriteGuard;
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use crate::context::TimugContext;
+use minijinja::{
+    value::{Enumerator, Object, ObjectRepr},
+    Environment, Error, ErrorKind, State, Value,
+};
+use parking_lot::{Mutex, RwLockWriteGuard};
+use serde::Deserialize;
+
+use crate::{config::MarkdownOptions, context::TimugContext, tools::render_markdown};
 
 pub mod alertbox;
 pub mod analytics;
 pub mod codeblock;
 pub mod contacts;
+pub mod figure;
+pub mod gallery;
 pub mod gist;
+pub mod include_code;
 pub mod info;
+pub mod meta_tags;
 pub mod projects;
 pub mod quote;
 pub mod reading;
+pub mod search;
 pub mod social_media_share;
 pub mod stats;
+pub mod structured_data;
 
 pub trait Extension<'a> {
     fn name() -> &'static str;
@@ -23,3 +39,90 @@ pub trait Extension<'a> {
     }
     fn after_body(_: &'_ mut RwLockWriteGuard<'static, TimugContext>) {}
 }
+
+// After body scripts of a single page, extensions add their scripts only to the pages that use them
+#[derive(Debug, Default)]
+pub struct PageAssets {
+    after_bodies: Mutex<Vec<Cow<'static, str>>>,
+}
+
+impl PageAssets {
+    pub fn new(after_bodies: &[Cow<'static, str>]) -> Self {
+        Self { after_bodies: Mutex::new(after_bodies.to_vec()) }
+    }
+
+    pub fn add_after_body(state: &State<'_, '_>, script: &'static str) {
+        if let Some(value) = state.lookup("after_bodies") {
+            Self::add(&value, script);
+        }
+    }
+
+    // Same as add_after_body, for the documents that are rendered outside of a template
+    pub fn add(value: &Value, script: &'static str) {
+        if let Some(assets) = value.downcast_object_ref::<PageAssets>() {
+            let mut after_bodies = assets.after_bodies.lock();
+            if !after_bodies.iter().any(|item| item == script) {
+                after_bodies.push(Cow::Borrowed(script));
+            }
+        }
+    }
+}
+
+impl Object for PageAssets {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Seq
+    }
+
+    fn get_value(self: &Arc<Self>, index: &Value) -> Option<Value> {
+        let after_bodies = self.after_bodies.lock();
+        Some(Value::from(after_bodies.get(index.as_usize()?)?.as_ref()))
+    }
+
+    fn enumerate(self: &Arc<Self>) -> Enumerator {
+        Enumerator::Seq(self.after_bodies.lock().len())
+    }
+}
+
+// Files of the extensions have to be inside the blog folder, absolute paths and ".." can not leave it
+pub fn source_path(blog_path: &Path, path: &str) -> Result<PathBuf, Error> {
+    let not_found = |err: std::io::Error| Error::new(ErrorKind::InvalidOperation, format!("Path not found: {}, {}", path, err));
+    let root = blog_path.canonicalize().map_err(not_found)?;
+    let file_path = root.join(path).canonicalize().map_err(not_found)?;
+
+    match file_path.starts_with(&root) {
+        true => Ok(file_path),
+        false => Err(Error::new(ErrorKind::InvalidOperation, format!("Path is outside of the blog folder: {}", path))),
+    }
+}
+
+// Renders the markdown content of the call blocks with the options of the document that is being rendered
+pub fn render_block_markdown(state: &State<'_, '_>, ctx: &TimugContext, content: &str) -> Result<String, Error> {
+    let options = match state.lookup("markdown") {
+        Some(value) if !value.is_undefined() && !value.is_none() => MarkdownOptions::deserialize(value).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?,
+        _ => ctx.config.markdown.clone(),
+    };
+
+    let lang = state.lookup("lang").unwrap_or_default();
+    let after_bodies = state.lookup("after_bodies").unwrap_or_default();
+    render_markdown(content, &options, lang.as_str().unwrap_or_default(), state.name(), &after_bodies).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let blog = dir.path().join("blog");
+        std::fs::create_dir_all(blog.join("code")).unwrap();
+        std::fs::write(blog.join("code").join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+
+        assert!(source_path(&blog, "code/main.rs").is_ok());
+        assert!(source_path(&blog, "code/../code/main.rs").is_ok());
+        assert!(source_path(&blog, "../secret.txt").is_err());
+        assert!(source_path(&blog, dir.path().join("secret.txt").to_str().unwrap()).is_err());
+        assert!(source_path(&blog, "code/missing.rs").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use minijinja::{
+    render,
+    value::{from_args, Kwargs, Object, ObjectRepr},
+    Error, ErrorKind, HtmlEscape, State, Value,
+};
+
+use crate::{consts::SEARCH_INDEX_FILE_NAME, context::get_context, render_hooks::escape_url};
+
+use super::{Extension, PageAssets};
+
+static HTML: &str = include_str!("search.html");
+
+const DEFAULT_LIMIT: usize = 20;
+
+// Title matches are ranked before the tag and body matches, every query word has to match
+static SCRIPT: &str = r#"<script>
+(() => {
+    const tokenize = (text) => text.toLowerCase().split(/[^\p{L}\p{N}]+/u).filter((token) => token.length >= 2);
+    const matches = (tokens, term) => tokens.some((token) => token.startsWith(term));
+
+    document.querySelectorAll('.search[data-index]').forEach((search) => {
+        const input = search.querySelector('input');
+        const results = search.querySelector('.search-results');
+        if (!results) return;
+
+        let index = null;
+        let version = 0;
+        const render = async () => {
+            const current = ++version;
+            const terms = tokenize(input.value);
+            index = index || fetch(search.dataset.index).then((response) => response.json());
+            const entries = terms.length === 0 ? [] : await index;
+            if (current !== version) return;
+
+            const found = entries.map((entry) => {
+                const fields = [[tokenize(entry.title || ''), 3], [tokenize((entry.tags || []).join(' ')), 2], [(entry.body || '').split(' '), 1]];
+                let score = 0;
+                for (const term of terms) {
+                    const termScore = fields.reduce((total, [tokens, weight]) => total + (matches(tokens, term) ? weight : 0), 0);
+                    if (termScore === 0) return null;
+                    score += termScore;
+                }
+                return { entry, score };
+            }).filter(Boolean).sort((a, b) => b.score - a.score).slice(0, Number(search.dataset.limit));
+
+            results.replaceChildren();
+            if (terms.length > 0 && found.length === 0) {
+                const item = document.createElement('li');
+                item.className = 'text-sm text-gray-500';
+                item.textContent = search.dataset.empty;
+                results.appendChild(item);
+            }
+
+            for (const { entry } of found) {
+                const item = document.createElement('li');
+                const link = document.createElement('a');
+                link.href = entry.url;
+                link.className = 'font-medium hover:text-indigo-600';
+                link.textContent = entry.title || entry.url;
+                item.appendChild(link);
+                if (entry.date) {
+                    const date = document.createElement('div');
+                    date.className = 'text-xs text-gray-400';
+                    date.textContent = entry.date;
+                    item.appendChild(date);
+                }
+                if (entry.summary) {
+                    const summary = document.createElement('p');
+                    summary.className = 'text-sm text-gray-600';
+                    summary.textContent = entry.summary;
+                    item.appendChild(summary);
+                }
+                results.appendChild(item);
+            }
+        };
+
+        input.addEventListener('input', render);
+        search.querySelector('form').addEventListener('submit', (event) => {
+            event.preventDefault();
+            render();
+        });
+
+        const query = new URLSearchParams(window.location.search).get('q');
+        if (query) {
+            input.value = query;
+            render();
+        }
+    });
+})();
+</script>"#;
+
+pub struct Search;
+
+impl std::fmt::Debug for Search {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "search")
+    }
+}
+
+impl Search {
+    pub fn new() -> Search {
+        Search {}
+    }
+}
+
+impl Object for Search {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(self: &Arc<Self>, state: &State<'_, '_>, args: &[Value]) -> Result<Value, Error> {
+        let (kwargs,): (Kwargs,) = from_args(args)?;
+        let ctx = get_context(snafu::location!()).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+        if ctx.config.search.is_none() {
+            log::warn!("Search index is not generated, add the search section to the config");
+        }
+
+        let url_prefix = state.lookup("url_prefix").unwrap_or_default();
+        let index_url = format!("{}/{}", url_prefix.as_str().unwrap_or_default(), SEARCH_INDEX_FILE_NAME);
+        let action = kwargs
+            .get::<Option<String>>("action")?
+            .map(|action| escape_url(&action));
+        let lang = state.lookup("lang").unwrap_or_default();
+        let lang = lang.as_str().unwrap_or_default();
+        let placeholder = HtmlEscape(
+            &kwargs
+                .get::<Option<String>>("placeholder")?
+                .unwrap_or_else(|| ctx.translate(lang, "search")),
+        )
+        .to_string();
+        let empty = HtmlEscape(
+            &kwargs
+                .get::<Option<String>>("empty")?
+                .unwrap_or_else(|| ctx.translate(lang, "search_empty")),
+        )
+        .to_string();
+        let _: Option<Value> = kwargs.get("caller")?;
+        let limit = kwargs
+            .get::<Option<usize>>("limit")?
+            .unwrap_or(DEFAULT_LIMIT);
+
+        // Search boxes with an action only submit the query to the results page
+        if action.is_none() {
+            PageAssets::add_after_body(state, SCRIPT);
+        }
+
+        let env = state.env();
+        let content = match ctx.get_template_page("search.html") {
+            Some(page) => render!(in env, page.content.as_str(), index_url => index_url, action => action, placeholder => placeholder, empty => empty, limit => limit),
+            None => render!(in env, HTML, index_url => index_url, action => action, placeholder => placeholder, empty => empty, limit => limit),
+        };
+
+        Ok(Value::from_safe_string(content))
+    }
+}
+
+impl<'a> Extension<'a> for Search {
+    fn name() -> &'static str {
+        "search"
+    }
+
+    fn register(env: &mut minijinja::Environment<'a>) {
+        env.add_global(Self::name(), Value::from_object(Self::new()));
+    }
+}
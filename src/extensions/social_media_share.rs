@@ -39,10 +39,10 @@ impl Object for SocialMediaShare {
 
         let content = match ctx.get_template_page("social_media_share.html") {
             Some(page) => {
-                render!(in env, page.content.as_str(), data => data, posts => ctx.posts_value, pages => ctx.pages_value)
+                render!(in env, page.content.as_str(), data => data, posts => state.lookup("posts"), pages => ctx.pages_value)
             }
             None => {
-                render!(in env, HTML, data => data, posts => ctx.posts_value, pages => ctx.pages_value)
+                render!(in env, HTML, data => data, posts => state.lookup("posts"), pages => ctx.pages_value)
             }
         };
 
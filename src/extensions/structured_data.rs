@@ -0,0 +1,311 @@
+use std::sync::Arc;
+
+use minijinja::{
+    value::{from_args, Object, ObjectRepr},
+    Error, ErrorKind, State, Value,
+};
+use serde::Serialize;
+
+use crate::{
+    consts::INDEX_PAGE,
+    context::{get_context, TimugContext},
+    feed::{absolute_url, post_authors},
+    page::Page,
+    post::Post,
+    search::{plain_text, summary},
+};
+
+use super::{
+    meta_tags::{absolute_link, SeoInfo},
+    Extension,
+};
+
+const SCHEMA_CONTEXT: &str = "https://schema.org";
+const DESCRIPTION_LENGTH: usize = 160;
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Person {
+    #[serde(rename = "@type")]
+    pub kind: &'static str,
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlogPosting {
+    pub headline: String,
+
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub url: String,
+    pub main_entity_of_page: String,
+    pub date_published: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_modified: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub author: Vec<Person>,
+
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub keywords: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    pub in_language: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSite {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub url: String,
+    pub in_language: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ListItem {
+    #[serde(rename = "@type")]
+    pub kind: &'static str,
+    pub position: usize,
+    pub name: String,
+    pub item: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreadcrumbList {
+    pub item_list_element: Vec<ListItem>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "@type")]
+pub enum Schema {
+    BlogPosting(BlogPosting),
+    WebSite(WebSite),
+    BreadcrumbList(BreadcrumbList),
+}
+
+#[derive(Serialize)]
+struct JsonLd<'a> {
+    #[serde(rename = "@context")]
+    context: &'static str,
+
+    #[serde(flatten)]
+    schema: &'a Schema,
+}
+
+impl Schema {
+    pub fn blog_posting(ctx: &TimugContext, post: &Post, default_image: Option<&str>) -> Self {
+        let url = absolute_url(&ctx.config.site_url, &post.url());
+        let mut author: Vec<Person> = post_authors(ctx, post)
+            .into_iter()
+            .map(|author| Person { kind: "Person", name: author.name, url: author.url })
+            .collect();
+
+        if author.is_empty() && !ctx.config.author.is_empty() {
+            author.push(Person {
+                kind: "Person",
+                name: ctx.config.author.clone(),
+                url: None,
+            });
+        }
+
+        let description = match post.description() {
+            Some(description) => description,
+            None => summary(&plain_text(&post.content(), &ctx.config.markdown.merge(&post.markdown())), DESCRIPTION_LENGTH),
+        };
+
+        Schema::BlogPosting(BlogPosting {
+            headline: post.title().clone(),
+            description,
+            main_entity_of_page: post
+                .canonical()
+                .map(|canonical| absolute_link(&ctx.config.site_url, &canonical))
+                .unwrap_or_else(|| url.clone()),
+            url,
+            date_published: post.date().to_rfc3339(),
+            date_modified: post.updated().map(|date| date.to_rfc3339()),
+            author,
+            keywords: post.tags().join(", "),
+            image: post
+                .cover()
+                .as_deref()
+                .or(default_image)
+                .map(|image| absolute_link(&ctx.config.site_url, image)),
+            in_language: post.lang(),
+        })
+    }
+
+    pub fn web_site(ctx: &TimugContext, lang: &str, url_prefix: &str) -> Self {
+        Schema::WebSite(WebSite {
+            name: ctx.config.title.clone(),
+            description: ctx.config.description.clone(),
+            url: absolute_url(&ctx.config.site_url, &format!("{}/", url_prefix)),
+            in_language: lang.to_string(),
+        })
+    }
+
+    // Ancestors of the page and the page itself, starting from the top level page
+    pub fn breadcrumb_list(site_url: &str, pages: &[&Page]) -> Self {
+        Schema::BreadcrumbList(BreadcrumbList {
+            item_list_element: pages
+                .iter()
+                .enumerate()
+                .map(|(index, page)| ListItem {
+                    kind: "ListItem",
+                    position: index + 1,
+                    name: page.title.clone(),
+                    item: absolute_url(site_url, &page.url()),
+                })
+                .collect(),
+        })
+    }
+
+    // Json is written into a script element, "<" is escaped so the content can not close the element
+    pub fn to_script(&self) -> Result<String, serde_json::Error> {
+        let json = serde_json::to_string(&JsonLd { context: SCHEMA_CONTEXT, schema: self })?;
+        Ok(format!(r#"<script type="application/ld+json">{}</script>"#, json.replace('<', "\\u003c")))
+    }
+}
+
+pub struct StructuredData;
+
+impl std::fmt::Debug for StructuredData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "structured_data")
+    }
+}
+
+impl StructuredData {
+    pub fn new() -> StructuredData {
+        StructuredData {}
+    }
+}
+
+impl Object for StructuredData {
+    fn repr(self: &Arc<Self>) -> ObjectRepr {
+        ObjectRepr::Plain
+    }
+
+    fn call(self: &Arc<Self>, state: &State<'_, '_>, args: &[Value]) -> Result<Value, Error> {
+        let _: () = from_args(args)?;
+        let ctx = get_context(snafu::location!()).map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?;
+
+        // Posts are articles, the home page is the site and nested custom pages have their breadcrumbs
+        let data = state.lookup("data").unwrap_or_default();
+        let schema = if let Some(post) = data.downcast_object_ref::<Post>() {
+            let seo = ctx.get_config::<SeoInfo>("seo").unwrap_or_default();
+            Some(Schema::blog_posting(&ctx, post, seo.image.as_deref()))
+        } else if let Some(page) = data.downcast_object_ref::<Page>() {
+            match page.lang {
+                None if page.slug == INDEX_PAGE => {
+                    let lang = state.lookup("lang").unwrap_or_default();
+                    let url_prefix = state.lookup("url_prefix").unwrap_or_default();
+                    Some(Schema::web_site(&ctx, lang.as_str().unwrap_or_default(), url_prefix.as_str().unwrap_or_default()))
+                }
+                Some(_) if page.parent.is_some() => {
+                    let ancestors = page.ancestors();
+                    let mut pages: Vec<&Page> = ancestors.iter().map(Arc::as_ref).collect();
+                    pages.push(page);
+                    Some(Schema::breadcrumb_list(&ctx.config.site_url, &pages))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        };
+
+        let content = match schema {
+            Some(schema) => schema
+                .to_script()
+                .map_err(|err| Error::new(ErrorKind::InvalidOperation, err.to_string()))?,
+            None => String::new(),
+        };
+
+        Ok(Value::from_safe_string(content))
+    }
+}
+
+impl<'a> Extension<'a> for StructuredData {
+    fn name() -> &'static str {
+        "structured_data"
+    }
+
+    fn register(env: &mut minijinja::Environment<'a>) {
+        env.add_global(Self::name(), Value::from_object(Self::new()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn test_blog_posting() {
+        let mut ctx = TimugContext::default();
+        ctx.config.site_url = "https://example.com/".to_string();
+        ctx.config.author = "Site Author".to_string();
+
+        let content = "---\ntitle: Hello\ndate: 2024-10-18 12:00:00\ntags: [rust, blog]\n---\nHello world.";
+        let post = Post::load_from_str(content, Path::new("hello.md")).unwrap();
+        let json = serde_json::to_value(Schema::blog_posting(&ctx, &post, Some("/assets/default.png"))).unwrap();
+        assert_eq!(json["author"], serde_json::json!([{ "@type": "Person", "name": "Site Author" }]));
+        assert_eq!(json["keywords"], "rust, blog");
+        assert_eq!(json["description"], "Hello world.");
+        assert_eq!(json["image"], "https://example.com/assets/default.png");
+        assert_eq!(json["url"], "https://example.com/2024/10/18/hello.html");
+        assert_eq!(json["mainEntityOfPage"], json["url"]);
+        assert_eq!(json["datePublished"], "2024-10-18T12:00:00+00:00");
+        assert!(json.get("dateModified").is_none());
+
+        let content = "---\ntitle: Hello\ndate: 2024-10-18 12:00:00\nupdated: 2024-11-01 09:30:00\ncover: assets/cover.png\ncanonical: https://dev.to/me/hello\n---\nHello";
+        let post = Post::load_from_str(content, Path::new("hello.md")).unwrap();
+        let json = serde_json::to_value(Schema::blog_posting(&ctx, &post, Some("/assets/default.png"))).unwrap();
+        assert_eq!(json["image"], "https://example.com/assets/cover.png");
+        assert_eq!(json["mainEntityOfPage"], "https://dev.to/me/hello");
+        assert_eq!(json["url"], "https://example.com/2024/10/18/hello.html");
+        assert_eq!(json["dateModified"], "2024-11-01T09:30:00+00:00");
+        assert!(json.get("keywords").is_none());
+    }
+
+    #[test]
+    fn test_to_script() {
+        let mut docs = Page::default();
+        docs.title = "Docs".to_string();
+        docs.slug = "docs/index".to_string();
+
+        let mut install = Page::default();
+        install.title = "Install </script>".to_string();
+        install.slug = "docs/install".to_string();
+        install.prefix = "/tr".to_string();
+
+        let script = Schema::breadcrumb_list("https://example.com/", &[&docs, &install])
+            .to_script()
+            .unwrap();
+        assert_eq!(
+            script,
+            r#"<script type="application/ld+json">{"@context":"https://schema.org","@type":"BreadcrumbList","itemListElement":[{"@type":"ListItem","position":1,"name":"Docs","item":"https://example.com/docs/index.html"},{"@type":"ListItem","position":2,"name":"Install \u003c/script>","item":"https://example.com/tr/docs/install.html"}]}</script>"#
+        );
+
+        let json = serde_json::to_value(JsonLd {
+            context: SCHEMA_CONTEXT,
+            schema: &Schema::WebSite(WebSite {
+                name: "Blog".to_string(),
+                description: String::new(),
+                url: absolute_url("https://example.com", "/"),
+                in_language: "en".to_string(),
+            }),
+        })
+        .unwrap();
+        assert_eq!(json, serde_json::json!({"@context": "https://schema.org", "@type": "WebSite", "name": "Blog", "url": "https://example.com/", "inLanguage": "en"}));
+    }
+}
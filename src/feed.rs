@@ -0,0 +1,351 @@
+use std::{path::PathBuf, sync::Arc};
+
+use console::style;
+use minijinja::{context, Value};
+use serde::Serialize;
+use snafu::ResultExt;
+
+use crate::{
+    context::{get_context, TimugContext},
+    engine::{RenderEngine, Renderable},
+    error::{JsonSerializationFailedSnafu, MarkdownRenderFailedSnafu},
+    post::Post,
+    search::{plain_text, summary},
+    tools::{get_file_name, url_encode},
+};
+
+const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+const JSON_FEED_SUMMARY_LENGTH: usize = 200;
+const LINK_ATTRIBUTES: [&str; 3] = ["href=\"", "src=\"", "srcset=\""];
+
+static ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="{{ lang }}">
+  <title>{{ title }}</title>
+  <link href="{{ url }}" rel="self" type="application/atom+xml"/>
+  <link href="{{ site_url }}"/>
+  <id>{{ url }}</id>
+  <updated>{{ updated }}</updated>
+  {%- if author %}
+  <author><name>{{ author }}</name></author>
+  {%- endif %}
+  {%- for entry in entries %}
+  <entry>
+    <title>{{ entry.title }}</title>
+    <link href="{{ entry.url }}"/>
+    <id>{{ entry.url }}</id>
+    <updated>{{ entry.updated }}</updated>
+    {%- for author in entry.authors %}
+    <author><name>{{ author }}</name></author>
+    {%- endfor %}
+    {%- for tag in entry.tags %}
+    <category term="{{ tag }}"/>
+    {%- endfor %}
+  </entry>
+  {%- endfor %}
+</feed>
+"#;
+
+// Xml text and attribute value, the result is marked as safe so the template does not escape it again
+fn escape(value: &str) -> Value {
+    Value::from_safe_string(
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&apos;"),
+    )
+}
+
+// Absolute link of the path, escaped for the xml attributes
+fn link(site_url: &str, path: &str) -> Value {
+    escape(&format!("{}{}", site_url.trim_end_matches('/'), path))
+}
+
+pub struct Feed {
+    pub title: String,
+    pub lang: String,
+    pub url: String,
+    pub posts: Vec<Arc<Post>>,
+}
+
+impl Renderable for Feed {
+    type Context = PathBuf;
+    fn render(&self, engine: &RenderEngine<'_>, file_name: PathBuf) -> crate::Result<()> {
+        let ctx = get_context(snafu::location!())?;
+        engine.update_status(style("Rendering feed").bold().cyan().to_string(), get_file_name(&file_name)?.as_str());
+
+        let entries = self
+            .posts
+            .iter()
+            .map(|post| {
+                let authors: Vec<Value> = post
+                    .author_ids()
+                    .iter()
+                    .filter_map(|id| ctx.config.authors.get(id))
+                    .map(|author| escape(&author.name))
+                    .collect();
+
+                context! {
+                    title => escape(&post.title()),
+                    url => link(&ctx.config.site_url, &post.url()),
+                    updated => post.date().to_rfc3339(),
+                    tags => post.tags().iter().map(|tag| escape(tag)).collect::<Vec<_>>(),
+                    authors => authors,
+                }
+            })
+            .collect::<Vec<Value>>();
+
+        let updated = self
+            .posts
+            .iter()
+            .map(|post| post.date())
+            .max()
+            .unwrap_or_else(chrono::Utc::now);
+
+        let content = engine
+            .env
+            .render_named_str(
+                "feed.xml",
+                ATOM,
+                context! {
+                    title => escape(&self.title),
+                    url => link(&ctx.config.site_url, &self.url),
+                    lang => escape(&self.lang),
+                    author => escape(&ctx.config.author),
+                    site_url => link(&ctx.config.site_url, "/"),
+                    updated => updated.to_rfc3339(),
+                    entries => entries,
+                },
+            )
+            .context(MarkdownRenderFailedSnafu { file_name: file_name.clone() })?;
+
+        engine.write(content, &file_name)?;
+        engine.update_status(style("Generated feed").bold().green().to_string(), get_file_name(&file_name)?.as_str());
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonFeedAuthor {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonFeedItem {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub content_html: String,
+    pub summary: String,
+    pub date_published: String,
+    pub language: String,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<JsonFeedAuthor>,
+}
+
+// JSON Feed 1.1 document, https://www.jsonfeed.org/version/1.1/
+#[derive(Debug, Serialize)]
+pub struct JsonFeedDocument {
+    pub version: &'static str,
+    pub title: String,
+    pub home_page_url: String,
+    pub feed_url: String,
+    pub language: String,
+
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<JsonFeedAuthor>,
+    pub items: Vec<JsonFeedItem>,
+}
+
+// Absolute link of the path for the json documents, no escaping is needed
+pub fn absolute_url(site_url: &str, path: &str) -> String {
+    format!("{}{}", site_url.trim_end_matches('/'), path)
+}
+
+// Root-relative href, src and srcset links of the rendered content are prefixed with the site url, readers show the content outside of the site
+pub fn absolute_links(site_url: &str, html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some((index, attribute)) = LINK_ATTRIBUTES
+        .iter()
+        .filter_map(|attribute| find_attribute(rest, attribute).map(|index| (index, *attribute)))
+        .min_by_key(|(index, _)| *index)
+    {
+        let start = index + attribute.len();
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = rest.find('"').unwrap_or(rest.len());
+        let value = &rest[..end];
+        match attribute {
+            "srcset=\"" => {
+                let candidates: Vec<String> = value
+                    .split(',')
+                    .map(|candidate| {
+                        let candidate = candidate.trim();
+                        let (link, descriptor) = candidate.split_once(' ').unwrap_or((candidate, ""));
+                        format!("{} {}", absolute_link(site_url, link), descriptor)
+                            .trim_end()
+                            .to_string()
+                    })
+                    .collect();
+                result.push_str(&candidates.join(", "));
+            }
+            _ => result.push_str(&absolute_link(site_url, value)),
+        }
+        rest = &rest[end..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+// Attribute names are matched after a whitespace, so "data-src" is not changed
+fn find_attribute(html: &str, attribute: &str) -> Option<usize> {
+    html.match_indices(attribute)
+        .map(|(index, _)| index)
+        .find(|index| html[..*index].ends_with(char::is_whitespace))
+}
+
+fn absolute_link(site_url: &str, link: &str) -> String {
+    match link.starts_with('/') && !link.starts_with("//") {
+        true => absolute_url(site_url, link),
+        false => link.to_string(),
+    }
+}
+
+// Authors of the post with their absolute author page and avatar links
+pub fn post_authors(ctx: &TimugContext, post: &Post) -> Vec<JsonFeedAuthor> {
+    post.author_ids()
+        .iter()
+        .filter_map(|id| ctx.config.authors.get(id).map(|author| (id, author)))
+        .map(|(id, author)| JsonFeedAuthor {
+            name: author.name.clone(),
+            url: Some(absolute_url(&ctx.config.site_url, &format!("/authors/{}.html", url_encode(id.clone())))),
+            avatar: author
+                .avatar
+                .as_ref()
+                .map(|avatar| match avatar.starts_with('/') {
+                    true => absolute_url(&ctx.config.site_url, avatar),
+                    false => avatar.clone(),
+                }),
+        })
+        .collect()
+}
+
+pub struct JsonFeed {
+    pub title: String,
+    pub lang: String,
+    pub url: String,
+    pub home_url: String,
+    pub posts: Vec<Arc<Post>>,
+}
+
+impl Renderable for JsonFeed {
+    type Context = PathBuf;
+    fn render(&self, engine: &RenderEngine<'_>, file_name: PathBuf) -> crate::Result<()> {
+        let ctx = get_context(snafu::location!())?;
+        engine.update_status(style("Rendering feed").bold().cyan().to_string(), get_file_name(&file_name)?.as_str());
+
+        let items = self
+            .posts
+            .iter()
+            .map(|post| {
+                let url = absolute_url(&ctx.config.site_url, &post.url());
+                let markdown = ctx.config.markdown.merge(&post.markdown());
+                Ok(JsonFeedItem {
+                    id: url.clone(),
+                    url,
+                    title: post.title().clone(),
+                    content_html: absolute_links(&ctx.config.site_url, &engine.content(&post.url())?),
+                    summary: summary(&plain_text(&post.content(), &markdown), JSON_FEED_SUMMARY_LENGTH),
+                    date_published: post.date().to_rfc3339(),
+                    language: post.lang(),
+                    tags: post.tags(),
+                    authors: post_authors(&ctx, post),
+                })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let feed = JsonFeedDocument {
+            version: JSON_FEED_VERSION,
+            title: self.title.clone(),
+            home_page_url: absolute_url(&ctx.config.site_url, &self.home_url),
+            feed_url: absolute_url(&ctx.config.site_url, &self.url),
+            language: self.lang.clone(),
+            description: ctx.config.description.clone(),
+            authors: match ctx.config.author.is_empty() {
+                true => Vec::new(),
+                false => vec![JsonFeedAuthor {
+                    name: ctx.config.author.clone(),
+                    url: None,
+                    avatar: None,
+                }],
+            },
+            items,
+        };
+
+        let content = serde_json::to_string_pretty(&feed).context(JsonSerializationFailedSnafu)?;
+        engine.write(content, &file_name)?;
+        engine.update_status(style("Generated feed").bold().green().to_string(), get_file_name(&file_name)?.as_str());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("Tom & \"Jerry\" <3 'cheese'").as_str(), Some("Tom &amp; &quot;Jerry&quot; &lt;3 &apos;cheese&apos;"));
+        assert_eq!(link("https://example.com/", "/tags/a&b.html").as_str(), Some("https://example.com/tags/a&amp;b.html"));
+    }
+
+    #[test]
+    fn test_absolute_links() {
+        let html = r##"<a href="/2024/1/2/hello.html#intro">Hello</a> <a href="#top">Top</a> <a href="https://example.org/">Out</a> <a href="//cdn.example.org/a.js">Cdn</a>
+<img data-src="/lazy.png" src="/assets/a.png" srcset="/assets/a-480.webp 480w, /assets/a-960.webp 960w">"##;
+        assert_eq!(
+            absolute_links("https://example.com/", html),
+            r##"<a href="https://example.com/2024/1/2/hello.html#intro">Hello</a> <a href="#top">Top</a> <a href="https://example.org/">Out</a> <a href="//cdn.example.org/a.js">Cdn</a>
+<img data-src="/lazy.png" src="https://example.com/assets/a.png" srcset="https://example.com/assets/a-480.webp 480w, https://example.com/assets/a-960.webp 960w">"##
+        );
+    }
+
+    #[test]
+    fn test_json_feed_item() {
+        let item = JsonFeedItem {
+            id: absolute_url("https://example.com/", "/2024/1/2/hello.html"),
+            url: absolute_url("https://example.com", "/2024/1/2/hello.html"),
+            title: "Hello".to_string(),
+            content_html: "<p>Hello</p>".to_string(),
+            summary: "Hello".to_string(),
+            date_published: "2024-01-02T10:00:00+00:00".to_string(),
+            language: "en".to_string(),
+            tags: Vec::new(),
+            authors: vec![JsonFeedAuthor { name: "Erhan".to_string(), url: None, avatar: None }],
+        };
+
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["id"], "https://example.com/2024/1/2/hello.html");
+        assert_eq!(json["url"], json["id"]);
+        assert!(json.get("tags").is_none());
+        assert_eq!(json["authors"], serde_json::json!([{ "name": "Erhan" }]));
+    }
+}
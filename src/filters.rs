@@ -1,26 +1,59 @@
-use chrono::NaiveDateTime;
-use minijinja::{Error, ErrorKind, Value};
+use chrono::{Locale, NaiveDateTime, Utc};
+use minijinja::{Error, ErrorKind, State, Value};
+use snafu::ResultExt;
 
-use crate::{engine::RenderEngine, tools::url_encode};
+use crate::{context::get_context, engine::RenderEngine, error::InvalidReferenceDateSnafu, i18n::fallback_languages, tools::url_encode};
 const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
+// Language codes that do not match with the "<code>_<CODE>" locale name
+const LOCALES: [(&str, &str); 14] = [
+    ("en", "en_US"),
+    ("ja", "ja_JP"),
+    ("ko", "ko_KR"),
+    ("zh", "zh_CN"),
+    ("sv", "sv_SE"),
+    ("da", "da_DK"),
+    ("el", "el_GR"),
+    ("cs", "cs_CZ"),
+    ("uk", "uk_UA"),
+    ("he", "he_IL"),
+    ("hi", "hi_IN"),
+    ("fa", "fa_IR"),
+    ("ar", "ar_SA"),
+    ("vi", "vi_VN"),
+];
+
 impl<'a> RenderEngine<'a> {
     pub fn build_filters(&mut self) {
         log::debug!("Build filters");
         self.env.add_filter("formatdatetime", Self::format_date);
+        self.env.add_filter("relative_date", Self::relative_date);
         self.env.add_filter("url_encode", Self::url_encode);
     }
 
-    fn format_date(value: Value, format: Option<Value>) -> Result<Value, Error> {
+    fn format_date(value: Value, format: Option<Value>, locale: Option<String>, state: &State) -> Result<Value, Error> {
         let format = match format {
             Some(format) => format.as_str().unwrap_or("%B %d, %Y").to_string(),
             _ => "%B %d, %Y".into(),
         };
 
+        // Explicit locale argument wins over the language of the current document
+        let locale = match locale {
+            Some(locale) => get_locale(&locale),
+            None => get_locale(
+                state
+                    .lookup("lang")
+                    .as_ref()
+                    .and_then(|lang| lang.as_str())
+                    .unwrap_or_default(),
+            ),
+        };
+
         if let Some(value) = value.as_str() {
             let date_info = NaiveDateTime::parse_from_str(value, FORMAT)
                 .map_err(|_| Error::new(ErrorKind::BadSerialization, format!("{} could converted into datetime", value)))?
-                .format(&format);
+                .and_utc()
+                .format_localized(&format, locale);
             let formated_datetime = format!("{}", date_info);
             Ok(formated_datetime.into())
         } else {
@@ -28,7 +61,85 @@ impl<'a> RenderEngine<'a> {
         }
     }
 
+    fn relative_date(value: String, state: &State) -> Result<Value, Error> {
+        let ctx = get_context(snafu::location!()).map_err(|error| Error::new(ErrorKind::InvalidOperation, error.to_string()))?;
+        let date = NaiveDateTime::parse_from_str(&value, FORMAT).map_err(|_| Error::new(ErrorKind::BadSerialization, format!("{} could converted into datetime", value)))?;
+        let reference = reference_date(ctx.config.reference_date.as_deref()).map_err(|error| Error::new(ErrorKind::InvalidOperation, error.to_string()))?;
+
+        let (key, count) = relative_duration(date, reference);
+        let lang = state.lookup("lang");
+        let languages = fallback_languages(lang.as_ref().and_then(|lang| lang.as_str()), &ctx.config.lang);
+
+        let text = ctx
+            .translations
+            .translate(&languages, key, Some(count), &[("count".to_string(), count.to_string())])
+            .unwrap_or_else(|| key.to_string());
+        Ok(Value::from(text))
+    }
+
     fn url_encode(url: String) -> Result<Value, Error> {
         Ok(Value::from_safe_string(url_encode(url)))
     }
 }
+
+fn get_locale(lang: &str) -> Locale {
+    let code = lang.replace('-', "_");
+    let name = match LOCALES.iter().find(|(lang, _)| *lang == code) {
+        Some((_, name)) => name.to_string(),
+        None if code.contains('_') => code,
+        None => format!("{}_{}", code, code.to_uppercase()),
+    };
+
+    Locale::try_from(name.as_str()).unwrap_or(Locale::POSIX)
+}
+
+// Relative dates are calculated against the configured reference date to keep the builds reproducible
+fn reference_date(reference_date: Option<&str>) -> crate::Result<NaiveDateTime> {
+    match reference_date {
+        Some(date) => NaiveDateTime::parse_from_str(date, FORMAT).context(InvalidReferenceDateSnafu { date }),
+        None => Ok(Utc::now().naive_utc()),
+    }
+}
+
+// Translation key and the count of the time unit, future dates are shown as "just now"
+fn relative_duration(date: NaiveDateTime, reference: NaiveDateTime) -> (&'static str, i64) {
+    let duration = reference - date;
+    match duration.num_minutes() {
+        minutes if minutes < 1 => ("just_now", 0),
+        minutes if minutes < 60 => ("minutes_ago", minutes),
+        _ => match duration.num_hours() {
+            hours if hours < 24 => ("hours_ago", hours),
+            _ => match duration.num_days() {
+                days if days < 30 => ("days_ago", days),
+                days if days < 365 => ("months_ago", days / 30),
+                days => ("years_ago", days / 365),
+            },
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_locale() {
+        assert_eq!(get_locale("tr"), Locale::tr_TR);
+        assert_eq!(get_locale("ja"), Locale::ja_JP);
+        assert_eq!(get_locale("pt-BR"), Locale::pt_BR);
+        assert_eq!(get_locale("xx"), Locale::POSIX);
+    }
+
+    #[test]
+    fn test_relative_duration() {
+        let reference = NaiveDateTime::parse_from_str("2024-10-18 12:00:00", FORMAT).unwrap();
+        let date = |value: &str| NaiveDateTime::parse_from_str(value, FORMAT).unwrap();
+
+        assert_eq!(relative_duration(date("2024-10-18 12:00:30"), reference), ("just_now", 0));
+        assert_eq!(relative_duration(date("2024-10-18 11:15:00"), reference), ("minutes_ago", 45));
+        assert_eq!(relative_duration(date("2024-10-18 09:00:00"), reference), ("hours_ago", 3));
+        assert_eq!(relative_duration(date("2024-10-15 12:00:00"), reference), ("days_ago", 3));
+        assert_eq!(relative_duration(date("2024-08-18 12:00:00"), reference), ("months_ago", 2));
+        assert_eq!(relative_duration(date("2022-10-18 12:00:00"), reference), ("years_ago", 2));
+    }
+}
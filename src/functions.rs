@@ -1,13 +1,14 @@
 use chrono::Datelike;
-use minijinja::{Error, ErrorKind, State, Value};
+use minijinja::{value::Kwargs, Error, ErrorKind, State, Value};
 
-use crate::{engine::RenderEngine, pages::Pages, posts::Posts};
+use crate::{consts::DEFAULT_LANGUAGE, context::get_context, engine::RenderEngine, i18n::fallback_languages, references};
 
 impl<'a> RenderEngine<'a> {
     pub fn build_functions(&mut self) {
         self.env.add_function("current_year", Self::current_year);
         self.env.add_function("post_url", Self::post_url);
         self.env.add_function("page_url", Self::page_url);
+        self.env.add_function("t", Self::translate);
     }
 
     fn current_year() -> Result<Value, Error> {
@@ -16,47 +17,51 @@ impl<'a> RenderEngine<'a> {
     }
 
     fn post_url(slug: String, state: &State) -> Result<Value, Error> {
-        let posts = match state.lookup("posts") {
-            Some(posts) => posts,
-            None => return Err(Error::new(ErrorKind::UndefinedError, "'posts' not found".to_string())),
-        };
-
-        let posts = match posts
-            .as_object()
-            .and_then(|obj| obj.downcast_ref::<Posts>())
-        {
-            Some(posts) => posts,
-            None => return Err(Error::new(ErrorKind::UndefinedError, "'posts' is not a Posts type".to_string())),
-        };
-
-        let post = match posts.posts.iter().find(|post| post.slug().as_str() == slug) {
-            Some(post) => post,
-            None => return Err(Error::new(ErrorKind::UndefinedError, format!("Post (slug: '{}') could not found", slug))),
-        };
-
-        let date = post.date();
-        Ok(Value::from_safe_string(format!("/{}/{}/{}/{}.html", date.year(), date.month(), date.day(), slug)))
+        let ctx = get_context(snafu::location!()).map_err(|error| Error::new(ErrorKind::InvalidOperation, error.to_string()))?;
+        let lang = state.lookup("lang");
+        match references::post_url(
+            &ctx,
+            &slug,
+            lang.as_ref()
+                .and_then(|lang| lang.as_str())
+                .unwrap_or(&ctx.config.lang),
+        ) {
+            Some(url) => Ok(Value::from_safe_string(url)),
+            None => Err(Error::new(ErrorKind::UndefinedError, format!("Post (slug: '{}') could not found", slug))),
+        }
     }
 
     fn page_url(slug: String, state: &State) -> Result<Value, Error> {
-        let pages = match state.lookup("pages") {
-            Some(pages) => pages,
-            None => return Err(Error::new(ErrorKind::UndefinedError, "'pages' not found".to_string())),
-        };
-
-        let pages = match pages
-            .as_object()
-            .and_then(|obj| obj.downcast_ref::<Pages>())
-        {
-            Some(pages) => pages,
-            None => return Err(Error::new(ErrorKind::UndefinedError, "'pages' is not a Posts type".to_string())),
-        };
-
-        let page = match pages.items.iter().find(|page| page.slug == slug) {
-            Some(page) => page,
-            None => return Err(Error::new(ErrorKind::UndefinedError, format!("Page (slug: '{}') could not found", slug))),
-        };
-
-        Ok(Value::from_safe_string(format!("/{}.html", page.slug)))
+        let ctx = get_context(snafu::location!()).map_err(|error| Error::new(ErrorKind::InvalidOperation, error.to_string()))?;
+        let lang = state.lookup("lang");
+        let lang = lang
+            .as_ref()
+            .and_then(|lang| lang.as_str())
+            .unwrap_or(&ctx.config.lang);
+        match ctx.pages.find(&slug, Some(lang)) {
+            Some(page) => Ok(Value::from_safe_string(references::page_url(&ctx, &page, lang))),
+            None => Err(Error::new(ErrorKind::UndefinedError, format!("Page (slug: '{}') could not found", slug))),
+        }
+    }
+
+    fn translate(key: String, kwargs: Kwargs, state: &State) -> Result<Value, Error> {
+        let ctx = get_context(snafu::location!()).map_err(|error| Error::new(ErrorKind::InvalidOperation, error.to_string()))?;
+        let lang = state.lookup("lang");
+        let languages = fallback_languages(lang.as_ref().and_then(|lang| lang.as_str()), &ctx.config.lang);
+
+        let count: Option<i64> = kwargs.get("count")?;
+        let mut args = Vec::new();
+        for name in kwargs.args() {
+            let value: Value = kwargs.get(name)?;
+            args.push((name.to_string(), value.to_string()));
+        }
+
+        match ctx.translations.translate(&languages, &key, count, &args) {
+            Some(text) => Ok(Value::from(text)),
+            None => {
+                log::warn!("Translation not found, key: {}, lang: {}", key, languages.first().unwrap_or(&DEFAULT_LANGUAGE));
+                Ok(Value::from(key))
+            }
+        }
     }
 }
@@ -0,0 +1,124 @@
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    html::{css_for_theme_with_class_style, styled_line_to_highlighted_html, ClassStyle, ClassedHTMLGenerator, IncludeBackground},
+    parsing::{SyntaxReference, SyntaxSet},
+    util::LinesWithEndings,
+};
+
+use crate::error::{HighlightFailedSnafu, HighlightThemeNotFoundSnafu};
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+// Class names are prefixed to not clash with the theme styles
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+pub const HIGHLIGHT_CSS_FILE_NAME: &str = "highlight.css";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighlightInfo {
+    #[serde(default = "default_theme")]
+    pub theme: String,
+
+    #[serde(default, rename = "inline-style")]
+    pub inline_style: bool,
+}
+
+fn default_theme() -> String {
+    "base16-ocean.dark".to_string()
+}
+
+impl Default for HighlightInfo {
+    fn default() -> Self {
+        Self { theme: default_theme(), inline_style: false }
+    }
+}
+
+impl HighlightInfo {
+    fn theme(&self) -> crate::Result<&'static Theme> {
+        THEME_SET
+            .themes
+            .get(&self.theme)
+            .context(HighlightThemeNotFoundSnafu { theme: self.theme.clone() })
+    }
+
+    // Background of the code block for the inline styled output
+    pub fn background(&self) -> crate::Result<Option<String>> {
+        if !self.inline_style {
+            return Ok(None);
+        }
+
+        Ok(self
+            .theme()?
+            .settings
+            .background
+            .map(|color| format!("background-color:#{:02x}{:02x}{:02x};", color.r, color.g, color.b)))
+    }
+
+    pub fn css(&self) -> crate::Result<String> {
+        css_for_theme_with_class_style(self.theme()?, CLASS_STYLE).context(HighlightFailedSnafu)
+    }
+
+    // Highlighted and escaped html of the code, unknown languages are rendered as plain text
+    pub fn highlight(&self, code: &str, lang: &str) -> crate::Result<String> {
+        let syntax = find_syntax(lang);
+
+        match self.inline_style {
+            true => {
+                let mut highlighter = HighlightLines::new(syntax, self.theme()?);
+                let mut html = String::new();
+                for line in LinesWithEndings::from(code) {
+                    let regions = highlighter
+                        .highlight_line(line, &SYNTAX_SET)
+                        .context(HighlightFailedSnafu)?;
+                    html.push_str(&styled_line_to_highlighted_html(&regions, IncludeBackground::No).context(HighlightFailedSnafu)?);
+                }
+                Ok(html)
+            }
+            false => {
+                let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAX_SET, CLASS_STYLE);
+                for line in LinesWithEndings::from(code) {
+                    generator
+                        .parse_html_for_line_which_includes_newline(line)
+                        .context(HighlightFailedSnafu)?;
+                }
+                Ok(generator.finalize())
+            }
+        }
+    }
+}
+
+fn find_syntax(lang: &str) -> &'static SyntaxReference {
+    SYNTAX_SET
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight() {
+        let info = HighlightInfo::default();
+        let html = info.highlight("fn main() {}\n", "rust").unwrap();
+        assert!(html.contains("<span class=\"hl-source hl-rust\">"));
+        assert!(info.css().unwrap().contains(".hl-"));
+
+        let html = info.highlight("a < b\n", "unknown").unwrap();
+        assert!(html.contains("a &lt; b"));
+
+        let info = HighlightInfo { inline_style: true, ..Default::default() };
+        let html = info.highlight("fn main() {}\n", "rs").unwrap();
+        assert!(html.contains("style=\"color:"));
+        assert!(info.background().unwrap().is_some());
+
+        let info = HighlightInfo { theme: "missing".to_string(), ..Default::default() };
+        assert!(info.css().is_err());
+    }
+}
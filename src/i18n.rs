@@ -0,0 +1,180 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use snafu::ResultExt;
+
+use crate::{
+    consts::DEFAULT_LANGUAGE,
+    error::YamlDeserializationFailedSnafu,
+    tools::{get_file_content, get_files},
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Message {
+    Text(String),
+    Plural {
+        #[serde(default)]
+        zero: Option<String>,
+
+        #[serde(default)]
+        one: Option<String>,
+        other: String,
+    },
+}
+
+impl Message {
+    pub fn get(&self, count: Option<i64>) -> &str {
+        match self {
+            Message::Text(text) => text,
+            Message::Plural { zero, one, other } => match count {
+                Some(0) => zero.as_deref().unwrap_or(other),
+                Some(1) => one.as_deref().unwrap_or(other),
+                _ => other,
+            },
+        }
+    }
+}
+
+// Built-in English strings, themes and sites can override or translate them
+static BUILTIN: &str = r#"
+copy: Copy
+search: Search
+search_empty: No results found
+callout_note: Note
+callout_tip: Tip
+callout_important: Important
+callout_warning: Warning
+callout_caution: Caution
+just_now: just now
+minutes_ago:
+  one: a minute ago
+  other: "{count} minutes ago"
+hours_ago:
+  one: an hour ago
+  other: "{count} hours ago"
+days_ago:
+  one: yesterday
+  other: "{count} days ago"
+months_ago:
+  one: a month ago
+  other: "{count} months ago"
+years_ago:
+  one: a year ago
+  other: "{count} years ago"
+"#;
+
+// Current document language is used first, then the site language and English
+pub fn fallback_languages<'a>(lang: Option<&'a str>, site_lang: &'a str) -> Vec<&'a str> {
+    [lang, Some(site_lang), Some(DEFAULT_LANGUAGE)]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[derive(Debug, Default)]
+pub struct Translations {
+    pub languages: HashMap<String, HashMap<String, Message>>,
+}
+
+impl Translations {
+    // Later folders override the keys of the previous ones, so the site translations win over the theme translations
+    pub fn load(folders: &[PathBuf]) -> crate::Result<Self> {
+        let mut translations = Self::default();
+        let builtin: HashMap<String, Message> = serde_yaml::from_str(BUILTIN).context(YamlDeserializationFailedSnafu { content: BUILTIN })?;
+        translations
+            .languages
+            .insert(DEFAULT_LANGUAGE.to_string(), builtin);
+
+        for folder in folders.iter().filter(|folder| folder.is_dir()) {
+            for file in get_files(folder, "yaml")? {
+                let lang = file
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                let content = get_file_content(&file)?;
+                let messages: HashMap<String, Message> = serde_yaml::from_str(&content).context(YamlDeserializationFailedSnafu { content })?;
+
+                log::debug!("Translations loaded: {}", file.display());
+                translations
+                    .languages
+                    .entry(lang)
+                    .or_default()
+                    .extend(messages);
+            }
+        }
+
+        Ok(translations)
+    }
+
+    // The first language that has the key is used
+    pub fn translate(&self, languages: &[&str], key: &str, count: Option<i64>, args: &[(String, String)]) -> Option<String> {
+        let message = languages.iter().find_map(|lang| {
+            self.languages
+                .get(*lang)
+                .and_then(|messages| messages.get(key))
+        })?;
+
+        let mut text = message.get(count).to_string();
+        for (name, value) in args.iter() {
+            text = text.replace(&format!("{{{}}}", name), value);
+        }
+
+        Some(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        let mut translations = Translations::default();
+        translations
+            .languages
+            .insert("en".to_string(), serde_yaml::from_str("next_post: Next Post\nposts:\n  zero: No posts\n  one: One post\n  other: \"{count} posts\"\nhello: Hello {name}").unwrap());
+        translations
+            .languages
+            .insert("tr".to_string(), serde_yaml::from_str("next_post: Sonraki Yazı\nposts: \"{count} yazı\"").unwrap());
+
+        assert_eq!(
+            translations
+                .translate(&["tr", "en"], "next_post", None, &[])
+                .as_deref(),
+            Some("Sonraki Yazı")
+        );
+        assert_eq!(
+            translations
+                .translate(&["tr", "en"], "hello", None, &[("name".to_string(), "Erhan".to_string())])
+                .as_deref(),
+            Some("Hello Erhan")
+        );
+        assert_eq!(
+            translations
+                .translate(&["tr", "en"], "posts", Some(3), &[("count".to_string(), "3".to_string())])
+                .as_deref(),
+            Some("3 yazı")
+        );
+        assert_eq!(
+            translations
+                .translate(&["en"], "posts", Some(0), &[])
+                .as_deref(),
+            Some("No posts")
+        );
+        assert_eq!(
+            translations
+                .translate(&["en"], "posts", Some(1), &[])
+                .as_deref(),
+            Some("One post")
+        );
+        assert_eq!(
+            translations
+                .translate(&["en"], "posts", Some(5), &[("count".to_string(), "5".to_string())])
+                .as_deref(),
+            Some("5 posts")
+        );
+        assert_eq!(translations.translate(&["en"], "missing", None, &[]), None);
+    }
+}
@@ -0,0 +1,411 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Cursor, Write},
+    path::{Path, PathBuf},
+};
+
+use image::{
+    codecs::{avif::AvifEncoder, jpeg::JpegEncoder, png::PngEncoder},
+    error::{EncodingError, ImageFormatHint},
+    imageops::FilterType,
+    DynamicImage, ImageError, ImageReader,
+};
+use sha2::{Digest, Sha256};
+use snafu::ResultExt;
+
+use crate::{
+    config::{ImageFormat, ImagesConfig},
+    consts::{ASSETS_PATH, RESIZED_IMAGES_PATH},
+    context::TimugContext,
+    error::{FileCreationFailedSnafu, FileNotFoundSnafu, FolderCreationFailedSnafu, ImageProcessingFailedSnafu},
+};
+
+// Faster AVIF encoding, the default speed makes the builds too slow
+const AVIF_SPEED: u8 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Jpeg,
+    Png,
+    Webp,
+    Avif,
+}
+
+impl Encoding {
+    // Only the raster formats are resized, svg and animated gif files are used as they are
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "jpg" | "jpeg" => Some(Encoding::Jpeg),
+            "png" => Some(Encoding::Png),
+            "webp" => Some(Encoding::Webp),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Encoding::Jpeg => "jpg",
+            Encoding::Png => "png",
+            Encoding::Webp => "webp",
+            Encoding::Avif => "avif",
+        }
+    }
+
+    fn mime(&self) -> &'static str {
+        match self {
+            Encoding::Jpeg => "image/jpeg",
+            Encoding::Png => "image/png",
+            Encoding::Webp => "image/webp",
+            Encoding::Avif => "image/avif",
+        }
+    }
+}
+
+impl From<ImageFormat> for Encoding {
+    fn from(format: ImageFormat) -> Self {
+        match format {
+            ImageFormat::Webp => Encoding::Webp,
+            ImageFormat::Avif => Encoding::Avif,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageSource {
+    pub mime: &'static str,
+    pub srcset: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponsiveImage {
+    pub width: u32,
+    pub height: u32,
+    pub srcset: String,
+    pub sources: Vec<ImageSource>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResizedImage {
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thumbnail {
+    pub thumbnail: ResizedImage,
+    pub original: ResizedImage,
+}
+
+struct SourceImage {
+    original: Encoding,
+    bytes: Vec<u8>,
+    hash: String,
+    width: u32,
+    height: u32,
+    stem: String,
+}
+
+impl SourceImage {
+    fn load(path: &Path) -> crate::Result<Option<Self>> {
+        let extension = path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+        let Some(original) = Encoding::from_extension(&extension) else {
+            return Ok(None);
+        };
+
+        let bytes = std::fs::read(path).context(FileNotFoundSnafu { path })?;
+        let hash: String = Sha256::digest(&bytes)[..8]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let (width, height) = ImageReader::new(Cursor::new(&bytes))
+            .with_guessed_format()
+            .map_err(ImageError::IoError)
+            .and_then(|reader| reader.into_dimensions())
+            .context(ImageProcessingFailedSnafu { path })?;
+
+        Ok(Some(Self {
+            original,
+            bytes,
+            hash,
+            width,
+            height,
+            stem: path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        }))
+    }
+}
+
+pub struct ImageProcessor {
+    pub config: ImagesConfig,
+    statics_path: PathBuf,
+    cache_path: PathBuf,
+    output_path: PathBuf,
+}
+
+impl ImageProcessor {
+    pub fn new(ctx: &TimugContext) -> Option<Self> {
+        Some(Self::with_config(ctx, ctx.config.images.clone()?))
+    }
+
+    pub fn with_config(ctx: &TimugContext, config: ImagesConfig) -> Self {
+        Self {
+            config,
+            statics_path: ctx.statics_path.clone(),
+            cache_path: ctx.cache_path.join("images"),
+            output_path: ctx
+                .config
+                .blog_path
+                .join(&ctx.config.deployment_folder)
+                .join(ASSETS_PATH)
+                .join(RESIZED_IMAGES_PATH),
+        }
+    }
+
+    // Assets folder file of the image url, other urls are not processed
+    fn source_path(&self, url: &str) -> Option<PathBuf> {
+        if url.contains(['?', '#']) {
+            return None;
+        }
+
+        let path = url
+            .strip_prefix("/")
+            .unwrap_or(url)
+            .strip_prefix(ASSETS_PATH)?
+            .strip_prefix("/")?;
+        let path = urlencoding::decode(path).ok()?;
+        let path = self.statics_path.join(path.as_ref());
+        path.is_file().then_some(path)
+    }
+
+    // Resized variants are cached by the content hash of the image, so only the new or changed images are encoded
+    pub fn process(&self, url: &str) -> crate::Result<Option<ResponsiveImage>> {
+        let Some(path) = self.source_path(url) else {
+            return Ok(None);
+        };
+
+        let Some(SourceImage { original, bytes, hash, width, height, stem }) = SourceImage::load(&path)? else {
+            return Ok(None);
+        };
+
+        let mut widths: Vec<u32> = self
+            .config
+            .widths
+            .iter()
+            .copied()
+            .filter(|size| *size < width)
+            .collect();
+        widths.push(width);
+        widths.sort_unstable();
+        widths.dedup();
+
+        let mut image: Option<DynamicImage> = None;
+        let mut encodings = vec![original];
+        encodings.extend(
+            self.config
+                .formats
+                .iter()
+                .map(|format| Encoding::from(*format))
+                .filter(|encoding| *encoding != original),
+        );
+
+        let mut srcsets = Vec::new();
+        for encoding in encodings.iter() {
+            let mut srcset = Vec::new();
+            for size in widths.iter() {
+                let file_name = format!("{}-{}-{}.{}", stem, hash, size, encoding.extension());
+                self.variant(&path, &bytes, &mut image, *size, *encoding, &file_name)?;
+                srcset.push(format!("/{}/{}/{} {}w", ASSETS_PATH, RESIZED_IMAGES_PATH, file_name, size));
+            }
+            srcsets.push((*encoding, srcset.join(", ")));
+        }
+
+        let srcset = srcsets.remove(0).1;
+        Ok(Some(ResponsiveImage {
+            width,
+            height,
+            srcset,
+            sources: srcsets
+                .into_iter()
+                .map(|(encoding, srcset)| ImageSource { mime: encoding.mime(), srcset })
+                .collect(),
+        }))
+    }
+
+    // Resized copy of the image and the original image for the full size view, the file is read and hashed once
+    pub fn thumbnail(&self, path: &Path, width: u32) -> crate::Result<Option<Thumbnail>> {
+        let Some(source) = SourceImage::load(path)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Thumbnail {
+            thumbnail: self.resize(path, &source, width)?,
+            original: ResizedImage {
+                url: self.original_url(path, &source)?,
+                width: source.width,
+                height: source.height,
+            },
+        }))
+    }
+
+    // Images of the assets folder are already copied into the site, other images are copied next to the resized ones
+    fn original_url(&self, path: &Path, source: &SourceImage) -> crate::Result<String> {
+        // Gallery folders are canonical paths, so the assets folder is compared in the same form
+        let statics_path = self
+            .statics_path
+            .canonicalize()
+            .unwrap_or_else(|_| self.statics_path.clone());
+        if let Ok(relative) = path
+            .strip_prefix(&self.statics_path)
+            .or_else(|_| path.strip_prefix(&statics_path))
+        {
+            let segments: Vec<String> = relative
+                .components()
+                .map(|component| urlencoding::encode(&component.as_os_str().to_string_lossy()).to_string())
+                .collect();
+            return Ok(format!("/{}/{}", ASSETS_PATH, segments.join("/")));
+        }
+
+        let file_name = format!("{}-{}.{}", source.stem, source.hash, source.original.extension());
+        let output_file = self.output_path.join(&file_name);
+        if !output_file.is_file() {
+            std::fs::create_dir_all(&self.output_path).context(FolderCreationFailedSnafu { path: self.output_path.clone() })?;
+            std::fs::write(&output_file, &source.bytes).context(FileCreationFailedSnafu { path: output_file.clone() })?;
+        }
+
+        Ok(format!("/{}/{}/{}", ASSETS_PATH, RESIZED_IMAGES_PATH, file_name))
+    }
+
+    // Copy of the image in its own format that is not wider than the given width
+    fn resize(&self, path: &Path, source: &SourceImage, width: u32) -> crate::Result<ResizedImage> {
+        let width = width.min(source.width);
+        let height = ((source.height as u64 * width as u64) / source.width.max(1) as u64).max(1) as u32;
+        let file_name = format!("{}-{}-{}.{}", source.stem, source.hash, width, source.original.extension());
+        self.variant(path, &source.bytes, &mut None, width, source.original, &file_name)?;
+
+        Ok(ResizedImage {
+            url: format!("/{}/{}/{}", ASSETS_PATH, RESIZED_IMAGES_PATH, file_name),
+            width,
+            height,
+        })
+    }
+
+    fn variant(&self, path: &Path, bytes: &[u8], image: &mut Option<DynamicImage>, width: u32, encoding: Encoding, file_name: &str) -> crate::Result<()> {
+        let cache_file = self.cache_path.join(file_name);
+        let output_file = self.output_path.join(file_name);
+
+        if !cache_file.is_file() {
+            if image.is_none() {
+                let decoded = ImageReader::new(Cursor::new(bytes))
+                    .with_guessed_format()
+                    .map_err(ImageError::IoError)
+                    .and_then(|reader| reader.decode())
+                    .context(ImageProcessingFailedSnafu { path })?;
+                *image = Some(decoded);
+            }
+
+            let resized = match image {
+                Some(image) if image.width() > width => image.resize(width, u32::MAX, FilterType::Lanczos3),
+                Some(image) => image.clone(),
+                None => return Ok(()),
+            };
+
+            // Encoded into a temporary file first, so a failed or interrupted build does not leave a broken cache entry
+            std::fs::create_dir_all(&self.cache_path).context(FolderCreationFailedSnafu { path: self.cache_path.clone() })?;
+            let temp_file = self.cache_path.join(format!("{}.tmp", file_name));
+            if let Err(error) = self.encode(&resized, encoding, &temp_file) {
+                let _ = std::fs::remove_file(&temp_file);
+                return Err(error).context(ImageProcessingFailedSnafu { path });
+            }
+
+            std::fs::rename(&temp_file, &cache_file).context(FileCreationFailedSnafu { path: cache_file.clone() })?;
+            log::debug!("Image variant created: {}", file_name);
+        }
+
+        if !output_file.is_file() {
+            std::fs::create_dir_all(&self.output_path).context(FolderCreationFailedSnafu { path: self.output_path.clone() })?;
+            std::fs::copy(&cache_file, &output_file).context(FileCreationFailedSnafu { path: output_file.clone() })?;
+        }
+
+        Ok(())
+    }
+
+    fn encode(&self, image: &DynamicImage, encoding: Encoding, path: &Path) -> Result<(), ImageError> {
+        let mut writer = BufWriter::new(File::create(path).map_err(ImageError::IoError)?);
+        match encoding {
+            Encoding::Jpeg => DynamicImage::from(image.to_rgb8()).write_with_encoder(JpegEncoder::new_with_quality(&mut writer, self.config.quality)),
+            Encoding::Png => image.write_with_encoder(PngEncoder::new(&mut writer)),
+            Encoding::Webp => {
+                // Lossy encoding with the configured quality, the lossless webp of a photo is often bigger than the jpeg
+                let rgba = image.to_rgba8();
+                let memory = webp::Encoder::from_rgba(rgba.as_raw(), rgba.width(), rgba.height())
+                    .encode_simple(false, self.config.quality as f32)
+                    .map_err(|error| ImageError::Encoding(EncodingError::new(ImageFormatHint::Exact(image::ImageFormat::WebP), format!("{:?}", error))))?;
+                writer.write_all(&memory).map_err(ImageError::IoError)
+            }
+            Encoding::Avif => DynamicImage::from(image.to_rgba8()).write_with_encoder(AvifEncoder::new_with_speed_quality(&mut writer, AVIF_SPEED, self.config.quality)),
+        }?;
+        writer.flush().map_err(ImageError::IoError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_process() {
+        let dir = tempdir().unwrap();
+        let statics_path = dir.path().join("assets");
+        std::fs::create_dir_all(statics_path.join("photos")).unwrap();
+        RgbImage::from_pixel(1000, 500, Rgb([200, 100, 50]))
+            .save(statics_path.join("photos").join("sunset.png"))
+            .unwrap();
+
+        let processor = ImageProcessor {
+            config: ImagesConfig { widths: vec![480, 2000], ..Default::default() },
+            statics_path,
+            cache_path: dir.path().join("cache"),
+            output_path: dir.path().join("public"),
+        };
+
+        assert_eq!(processor.process("https://example.com/image.png").unwrap(), None);
+        assert_eq!(processor.process("/assets/photos/missing.png").unwrap(), None);
+
+        let image = processor
+            .process("/assets/photos/sunset.png")
+            .unwrap()
+            .unwrap();
+        assert_eq!((image.width, image.height), (1000, 500));
+        assert_eq!(image.srcset.matches(".png").count(), 2);
+        assert!(image.srcset.contains(" 480w") && image.srcset.contains(" 1000w"));
+        assert_eq!(image.sources.len(), 1);
+        assert_eq!(image.sources[0].mime, "image/webp");
+
+        let small = std::fs::read_dir(dir.path().join("public"))
+            .unwrap()
+            .flatten()
+            .find(|entry| entry.file_name().to_string_lossy().ends_with("-480.webp"))
+            .unwrap();
+        assert_eq!(image::image_dimensions(small.path()).unwrap(), (480, 240));
+        assert_eq!(std::fs::read_dir(dir.path().join("cache")).unwrap().count(), 4);
+
+        let Thumbnail { thumbnail, original } = processor
+            .thumbnail(&dir.path().join("assets").join("photos").join("sunset.png"), 200)
+            .unwrap()
+            .unwrap();
+        assert_eq!((thumbnail.width, thumbnail.height), (200, 100));
+        assert!(thumbnail.url.starts_with("/assets/resized/sunset-") && thumbnail.url.ends_with("-200.png"));
+        assert_eq!(original.url, "/assets/photos/sunset.png");
+        assert_eq!((original.width, original.height), (1000, 500));
+    }
+}
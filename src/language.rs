@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use minijinja::Value;
+use serde::{Deserialize, Serialize};
+use snafu::OptionExt;
+
+use crate::{config::TimugConfig, error::FailedToAddTagSnafu, posts::Posts, tags::Tags};
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Language {
+    pub code: String,
+    pub name: String,
+    pub prefix: String,
+}
+
+impl Language {
+    pub fn all(config: &TimugConfig) -> Vec<Language> {
+        let mut languages = vec![Self::new(config, &config.lang, true)];
+        let mut others: Vec<Language> = config
+            .languages
+            .keys()
+            .filter(|code| **code != config.lang)
+            .map(|code| Self::new(config, code, false))
+            .collect();
+
+        others.sort_by(|x, y| x.code.cmp(&y.code));
+        languages.extend(others);
+        languages
+    }
+
+    fn new(config: &TimugConfig, code: &str, default: bool) -> Self {
+        let info = config.languages.get(code);
+        let prefix = match info.and_then(|info| info.prefix.as_deref()) {
+            Some(prefix) => prefix.trim_matches('/').to_string(),
+            None if default => String::new(),
+            None => code.to_string(),
+        };
+
+        Self {
+            code: code.to_string(),
+            name: info
+                .and_then(|info| info.name.clone())
+                .unwrap_or_else(|| code.to_string()),
+            prefix: match prefix.is_empty() {
+                true => prefix,
+                false => format!("/{}", prefix),
+            },
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.prefix, path)
+    }
+}
+
+// Splits the language suffix from the file name, "post.tr" is a Turkish version of the "post"
+pub fn split_language<'a>(name: &'a str, languages: &[Language]) -> Option<(&'a str, &'a str)> {
+    let (name, code) = name.rsplit_once('.')?;
+    languages
+        .iter()
+        .any(|language| language.code == code)
+        .then_some((name, code))
+}
+
+// Detects the document language and its output prefix from the front matter or the file name suffix
+pub fn detect_language(lang: Option<&str>, name: &str, languages: &[Language], document: &str) -> (String, String) {
+    let code = lang.or_else(|| split_language(name, languages).map(|(_, code)| code));
+    let language = match code {
+        Some(code) => match languages.iter().find(|language| language.code == code) {
+            Some(language) => Some(language),
+            None => {
+                log::warn!("Language '{}' is not configured, document: {}", code, document);
+                None
+            }
+        },
+        None => None,
+    };
+
+    match language.or_else(|| languages.first()) {
+        Some(language) => (language.code.clone(), language.prefix.clone()),
+        None => (code.unwrap_or_default().to_string(), String::new()),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LanguageContext {
+    pub language: Language,
+    pub posts: Arc<Posts>,
+    pub posts_value: Value,
+    pub tags: Tags,
+}
+
+impl LanguageContext {
+    pub fn new(language: Language, posts: &Posts) -> crate::Result<Self> {
+        let posts = Arc::new(posts.filter(&language.code));
+        let mut tags = Tags::default();
+
+        for post in posts.posts.iter() {
+            for tag in post.tags() {
+                tags.add(tag.clone(), post.clone())
+                    .context(FailedToAddTagSnafu { tag })?;
+            }
+        }
+
+        Ok(Self {
+            language,
+            posts_value: Value::from_dyn_object(posts.clone()),
+            posts,
+            tags,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::config::LanguageInfo;
+
+    fn create_config() -> TimugConfig {
+        TimugConfig {
+            lang: "en".to_string(),
+            languages: HashMap::from([("tr".to_string(), LanguageInfo { name: Some("Türkçe".to_string()), prefix: None }), ("de".to_string(), LanguageInfo { name: None, prefix: Some("/deutsch/".to_string()) })]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_all() {
+        let languages = Language::all(&create_config());
+        assert_eq!(languages.len(), 3);
+        assert_eq!(
+            languages[0],
+            Language {
+                code: "en".to_string(),
+                name: "en".to_string(),
+                prefix: "".to_string()
+            }
+        );
+        assert_eq!(
+            languages[1],
+            Language {
+                code: "de".to_string(),
+                name: "de".to_string(),
+                prefix: "/deutsch".to_string()
+            }
+        );
+        assert_eq!(
+            languages[2],
+            Language {
+                code: "tr".to_string(),
+                name: "Türkçe".to_string(),
+                prefix: "/tr".to_string()
+            }
+        );
+        assert_eq!(languages[2].url("/about.html"), "/tr/about.html");
+    }
+
+    #[test]
+    fn test_detect_language() {
+        let languages = Language::all(&create_config());
+        assert_eq!(split_language("post.tr", &languages), Some(("post", "tr")));
+        assert_eq!(split_language("post.fr", &languages), None);
+        assert_eq!(split_language("post", &languages), None);
+
+        assert_eq!(detect_language(None, "post", &languages, "post.md"), ("en".to_string(), "".to_string()));
+        assert_eq!(detect_language(None, "post.tr", &languages, "post.tr.md"), ("tr".to_string(), "/tr".to_string()));
+        assert_eq!(detect_language(Some("de"), "post.tr", &languages, "post.tr.md"), ("de".to_string(), "/deutsch".to_string()));
+        assert_eq!(detect_language(Some("fr"), "post", &languages, "post.md"), ("en".to_string(), "".to_string()));
+    }
+}
@@ -1,4 +1,8 @@
+mod api;
 mod application;
+mod author;
+mod authors;
+mod check_links;
 mod cli;
 mod config;
 mod consts;
@@ -7,29 +11,39 @@ mod document;
 mod engine;
 mod error;
 mod extensions;
+mod feed;
 mod filters;
 mod functions;
+mod highlight;
+mod i18n;
+mod images;
+mod language;
+mod math;
 mod page;
 mod pages;
 mod post;
 mod posts;
+mod references;
+mod render_hooks;
+mod search;
 mod server;
 mod tag;
 mod tags;
 mod template;
 mod tools;
+mod wikilinks;
 
-use application::{create_page, create_post, execute_template, start_create_new_project, start_deploy_pages, start_server};
+use application::{create_page, create_post, execute_template, start_check_links, start_create_new_project, start_deploy_pages, start_server};
 use clap::Parser;
 use cli::{CreateType, LogLevel};
 use env_logger::fmt::style;
 use log::{Level, LevelFilter};
-use std::io::Write;
+use std::{io::Write, process::ExitCode};
 
 pub use crate::error::Error;
 pub use crate::error::Result;
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     let cli = cli::Cli::parse();
 
     let level_filter = match cli.log {
@@ -64,9 +78,11 @@ fn main() -> Result<()> {
         .init();
 
     log::info!("Timug v{}", env!("CARGO_PKG_VERSION"));
+    let is_check_links = matches!(cli.command, cli::Commands::CheckLinks { .. });
     let result = match cli.command {
         cli::Commands::Init => start_create_new_project(cli.path),
-        cli::Commands::Deploy { draft } => start_deploy_pages(cli.path, draft),
+        cli::Commands::Deploy { draft, preview } => start_deploy_pages(cli.path, draft, preview),
+        cli::Commands::CheckLinks { draft } => start_check_links(cli.path, draft),
         cli::Commands::Server { port, draft } => start_server(cli.path, port, draft),
         cli::Commands::Create { _type, title, draft } => match _type {
             CreateType::Post => create_post(cli.path, title, draft),
@@ -77,7 +93,12 @@ fn main() -> Result<()> {
 
     if let Err(ref e) = result {
         log::error!("{}", e);
+
+        // Failed link checks exit with a non-zero code, so the CI builds can detect them
+        if is_check_links {
+            return ExitCode::FAILURE;
+        }
     }
 
-    Ok(())
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,47 @@
+use latex2mathml::{latex_to_mathml, DisplayStyle};
+use pulldown_cmark::Event;
+use snafu::ResultExt;
+
+use crate::error::MathRenderFailedSnafu;
+
+fn to_mathml(formula: &str, style: DisplayStyle, document: &str) -> crate::Result<String> {
+    latex_to_mathml(formula, style)
+        .map_err(Box::new)
+        .context(MathRenderFailedSnafu { formula, document })
+}
+
+// Inline and display math are converted into MathML, so no client side script is needed
+pub fn render_math<'a>(events: impl Iterator<Item = Event<'a>>, document: &str) -> crate::Result<Vec<Event<'a>>> {
+    events
+        .map(|event| match event {
+            Event::InlineMath(formula) => Ok(Event::InlineHtml(to_mathml(&formula, DisplayStyle::Inline, document)?.into())),
+            Event::DisplayMath(formula) => Ok(Event::InlineHtml(to_mathml(&formula, DisplayStyle::Block, document)?.into())),
+            event => Ok(event),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::MarkdownOptions, tools::parse_markdown};
+
+    #[test]
+    fn test_render_math() {
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(
+            &mut html,
+            render_math(parse_markdown("Euler $e^{i\\pi} + 1 = 0$\n\n$$\\frac{a}{b}$$", &MarkdownOptions { math: Some(true), ..Default::default() }), "test.md")
+                .unwrap()
+                .into_iter(),
+        );
+        assert!(html.contains("<math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"inline\">"));
+        assert!(html.contains("<math xmlns=\"http://www.w3.org/1998/Math/MathML\" display=\"block\">"));
+        assert!(!html.contains('$'));
+
+        let error = render_math(parse_markdown("$\\begin{foo}x\\end{foo}$", &MarkdownOptions { math: Some(true), ..Default::default() }), "test.md").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("formula: \\begin{foo}x\\end{foo}, document: test.md"));
+    }
+}
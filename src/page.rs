@@ -5,17 +5,20 @@ use std::{
     sync::Arc,
 };
 
-use minijinja::{value::Object, Value};
+use minijinja::{context, value::Object, Value};
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 
 use crate::{
-    consts::PAGE_HTML,
+    config::MarkdownOptions,
+    consts::{INDEX_PAGE, PAGE_HTML},
     context::get_context,
     document::{DocumentContext, DocumentType},
     engine::{RenderEngine, Renderable},
-    error::{PathBufParseSnafu, YamlDeserializationFailedSnafu},
+    error::{FolderCreationFailedSnafu, PathBufParseSnafu, YamlDeserializationFailedSnafu},
+    language::{detect_language, split_language, Language},
     tools::{get_file_content, get_file_name, get_path, parse_yaml_front_matter},
+    wikilinks::backlinks_of,
 };
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -44,6 +47,30 @@ pub struct Page {
     #[serde(default)]
     pub render: bool,
 
+    #[serde(default, alias = "template")]
+    pub layout: Option<String>,
+
+    #[serde(default)]
+    pub markdown: MarkdownOptions,
+
+    #[serde(default)]
+    pub lang: Option<String>,
+
+    #[serde(default)]
+    pub translation_key: Option<String>,
+
+    #[serde(skip)]
+    pub prefix: String,
+
+    #[serde(skip)]
+    pub folder: String,
+
+    #[serde(skip)]
+    pub parent: Option<String>,
+
+    #[serde(skip)]
+    pub children: Vec<String>,
+
     #[serde(flatten)]
     other: HashMap<String, serde_yaml::value::Value>,
 }
@@ -86,7 +113,98 @@ impl Page {
         Ok(page)
     }
 
-    fn inner_render(&self, engine: &RenderEngine<'_>) -> crate::Result<()> {
+    pub fn set_language(&mut self, languages: &[Language]) {
+        let (lang, prefix) = detect_language(self.lang.as_deref(), &self.slug, languages, &self.path);
+        if let Some((slug, _)) = split_language(&self.slug, languages) {
+            self.slug = slug.to_string();
+        }
+
+        self.lang = Some(lang);
+        self.prefix = prefix;
+    }
+
+    pub fn translation_key(&self) -> String {
+        self.translation_key
+            .clone()
+            .unwrap_or_else(|| self.slug.clone())
+    }
+
+    pub fn set_folder(&mut self, folder: String) {
+        if !folder.is_empty() {
+            self.slug = format!("{}/{}", folder, self.slug);
+        }
+        self.folder = folder;
+    }
+
+    pub fn is_index(&self) -> bool {
+        self.slug == INDEX_PAGE || self.slug.ends_with(&format!("/{}", INDEX_PAGE))
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}/{}.html", self.prefix, self.slug)
+    }
+
+    // Theme pages are rendered for every language, custom pages only have their own language
+    fn translations(&self) -> Vec<Value> {
+        let ctx = match get_context(snafu::location!()) {
+            Ok(ctx) => ctx,
+            Err(_) => return Vec::new(),
+        };
+
+        let key = self.translation_key();
+        ctx.languages
+            .iter()
+            .filter_map(|language| match &self.lang {
+                Some(_) => ctx
+                    .pages
+                    .items
+                    .iter()
+                    .find(|page| page.lang.as_deref() == Some(language.code.as_str()) && page.translation_key() == key)
+                    .map(|page| context! { lang => language.code, hreflang => language.code, name => language.name, title => page.title, url => Value::from_safe_string(page.url()) }),
+                None => Some(context! { lang => language.code, hreflang => language.code, name => language.name, title => self.title, url => Value::from_safe_string(language.url(&format!("/{}.html", self.slug))) }),
+            })
+            .collect()
+    }
+
+    pub fn ancestors(&self) -> Vec<Arc<Page>> {
+        let ctx = match get_context(snafu::location!()) {
+            Ok(ctx) => ctx,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut ancestors = Vec::new();
+        let mut parent = self.parent.clone();
+
+        while let Some(page) = parent.and_then(|slug| ctx.pages.find(&slug, self.lang.as_deref())) {
+            parent = page.parent.clone();
+            ancestors.insert(0, page);
+        }
+
+        ancestors
+    }
+
+    fn lookup(&self, slugs: &[String]) -> Vec<Arc<Page>> {
+        match get_context(snafu::location!()) {
+            Ok(ctx) => slugs
+                .iter()
+                .filter_map(|slug| ctx.pages.find(slug, self.lang.as_deref()))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn breadcrumbs(self: &Arc<Self>) -> Value {
+        let mut breadcrumbs = self.ancestors();
+        breadcrumbs.push(self.clone());
+
+        Value::from_iter(
+            breadcrumbs
+                .iter()
+                .map(|page| context! { title => page.title, url => page.url(), slug => page.slug }),
+        )
+    }
+
+    fn inner_render(self: &Arc<Self>, engine: &RenderEngine<'_>, language: &Language) -> crate::Result<()> {
         let ctx = get_context(snafu::location!())?;
         if !ctx.draft && self.draft {
             return Ok(());
@@ -97,18 +215,32 @@ impl Page {
             .config
             .blog_path
             .join(ctx.config.deployment_folder.clone());
+        let target_file_path = publish_path.join(
+            language
+                .url(&format!("/{}.html", self.slug))
+                .trim_start_matches('/'),
+        );
+
+        if let Some(target_folder) = target_file_path.parent() {
+            std::fs::create_dir_all(target_folder).context(FolderCreationFailedSnafu { path: target_folder })?;
+        }
 
+        let template = engine.get_layout(self.layout.as_deref(), PAGE_HTML, &source_path)?;
         let render_ctx = DocumentContext {
             source_file_path: source_path.clone(),
-            target_file_path: publish_path.join(self.file_name.replace(".md", ".html")),
-            template: PAGE_HTML.to_string(),
+            target_file_path,
+            template,
+            lang: language.code.clone(),
+            markdown: ctx.config.markdown.merge(&self.markdown),
             title: self.title.clone(),
             index: 0,
-            data: (),
+            data: Value::from_dyn_object(self.clone()),
         };
 
         // Render the page
-        self.page_type.render(engine, render_ctx)
+        let content = self.page_type.render(engine, render_ctx)?;
+        engine.set_content(language.url(&format!("/{}.html", self.slug)), content);
+        Ok(())
     }
 }
 
@@ -142,19 +274,36 @@ impl Object for Page {
             "slug" => Some(Value::from(&self.slug)),
             "path" => Some(Value::from(&self.path)),
             "draft" => Some(Value::from(self.draft)),
+            "layout" => self.layout.as_deref().map(Value::from),
+            "url" => Some(Value::from(self.url())),
+            "lang" => self.lang.as_deref().map(Value::from),
+            "translation_key" => Some(Value::from(self.translation_key())),
+            "translations" => Some(Value::from(self.translations())),
+            "parent" => self
+                .lookup(self.parent.as_slice())
+                .pop()
+                .map(Value::from_dyn_object),
+            "children" => Some(Value::from_iter(
+                self.lookup(&self.children)
+                    .into_iter()
+                    .map(Value::from_dyn_object),
+            )),
+            "ancestors" => Some(Value::from_iter(self.ancestors().into_iter().map(Value::from_dyn_object))),
+            "breadcrumbs" => Some(self.breadcrumbs()),
+            "backlinks" => Some(backlinks_of(&self.url())),
             _ => None,
         }
     }
 }
 
-impl Renderable for Page {
-    type Context = ();
-    fn render(&self, engine: &RenderEngine<'_>, _: Self::Context) -> crate::Result<()> {
+impl Renderable for Arc<Page> {
+    type Context = Language;
+    fn render(&self, engine: &RenderEngine<'_>, language: Language) -> crate::Result<()> {
         if !self.render {
             return Ok(());
         }
 
-        match self.inner_render(engine) {
+        match self.inner_render(engine, &language) {
             Err(err) => {
                 log::error!("Failed to render page: {}", self.path);
                 log::error!("{}", err);
@@ -1,4 +1,8 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
 
 use minijinja::{
     value::{Enumerator, Object},
@@ -6,7 +10,15 @@ use minijinja::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{context::get_context, page::Page, tools::get_files};
+use crate::{
+    consts::INDEX_PAGE,
+    context::get_context,
+    page::Page,
+    tools::{get_files, get_files_recursive},
+};
+
+// Language and slug of the page, translations share the same slug
+type PageKey = (Option<String>, String);
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Pages {
@@ -32,29 +44,118 @@ impl Pages {
 
     pub fn load_custom_pages(&mut self) -> crate::Result<()> {
         let ctx = get_context(snafu::location!())?;
-        let html_files = get_files(&ctx.pages_path, "html")?;
-        let md_files = get_files(&ctx.pages_path, "md")?;
+        let html_files = get_files_recursive(&ctx.pages_path, "html")?;
+        let md_files = get_files_recursive(&ctx.pages_path, "md")?;
+        let mut pages = Vec::new();
 
-        for file in html_files {
+        for file in html_files.into_iter().chain(md_files) {
             let mut page = Page::load_from_path(&file)?;
             page.render = true;
-            self.items.push(page.into());
+            page.set_language(&ctx.languages);
+            page.set_folder(Self::get_folder(&ctx.pages_path, &file));
+            pages.push(page);
         }
 
-        for file in md_files {
-            let mut page = Page::load_from_path(&file)?;
-            page.render = true;
-            self.items.push(page.into());
-        }
+        Self::link(&mut pages, ctx.draft);
+        self.items.extend(pages.into_iter().map(Arc::new));
 
         Ok(())
     }
 
+    // Name is the file name with its folder, so "docs/index.md" and "blog/index.md" are different pages
     pub fn get(&self, name: &str) -> Option<Arc<Page>> {
         self.items
             .iter()
-            .find(|page| page.file_name == name)
+            .find(|page| match page.folder.is_empty() {
+                true => page.file_name == name,
+                false => format!("{}/{}", page.folder, page.file_name) == name,
+            })
+            .cloned()
+    }
+
+    // Same language version of the page is preferred, theme pages have no language
+    pub fn find(&self, slug: &str, lang: Option<&str>) -> Option<Arc<Page>> {
+        self.items
+            .iter()
+            .find(|page| page.slug == slug && page.lang.as_deref() == lang)
+            .or_else(|| self.items.iter().find(|page| page.slug == slug))
+            .cloned()
+    }
+
+    // Custom pages of the language that are rendered, theme pages are not included
+    pub fn published(&self, lang: &str, draft: bool) -> Vec<Arc<Page>> {
+        self.items
+            .iter()
+            .filter(|page| page.render && (draft || !page.draft) && page.lang.as_deref() == Some(lang))
             .cloned()
+            .collect()
+    }
+
+    fn get_folder(pages_path: &Path, file: &Path) -> String {
+        file.parent()
+            .and_then(|folder| folder.strip_prefix(pages_path).ok())
+            .map(|folder| {
+                folder
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy().to_lowercase())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or_default()
+    }
+
+    fn parent_folder(folder: &str) -> &str {
+        folder
+            .rsplit_once('/')
+            .map(|(parent, _)| parent)
+            .unwrap_or_default()
+    }
+
+    // Index page of the closest folder is the parent of the page
+    fn find_parent(slugs: &HashSet<PageKey>, page: &Page) -> Option<String> {
+        let mut folder = match page.is_index() {
+            true => Self::parent_folder(&page.folder),
+            false => page.folder.as_str(),
+        };
+
+        while !folder.is_empty() {
+            let slug = format!("{}/{}", folder, INDEX_PAGE);
+            if slug != page.slug && slugs.contains(&(page.lang.clone(), slug.clone())) {
+                return Some(slug);
+            }
+
+            folder = Self::parent_folder(folder);
+        }
+
+        None
+    }
+
+    fn link(pages: &mut [Page], draft: bool) {
+        let slugs: HashSet<PageKey> = pages
+            .iter()
+            .map(|page| (page.lang.clone(), page.slug.clone()))
+            .collect();
+
+        for page in pages.iter_mut() {
+            page.parent = Self::find_parent(&slugs, page);
+        }
+
+        let mut children: HashMap<PageKey, Vec<(String, String)>> = HashMap::new();
+        for page in pages.iter().filter(|page| draft || !page.draft) {
+            if let Some(parent) = &page.parent {
+                children
+                    .entry((page.lang.clone(), parent.clone()))
+                    .or_default()
+                    .push((page.title.clone(), page.slug.clone()));
+            }
+        }
+
+        for page in pages.iter_mut() {
+            if let Some(mut items) = children.remove(&(page.lang.clone(), page.slug.clone())) {
+                items.sort_unstable();
+                page.children = items.into_iter().map(|(_, slug)| slug).collect();
+            }
+        }
     }
 }
 
@@ -71,3 +172,107 @@ impl Object for Pages {
         Enumerator::Seq(self.items.len())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::Language;
+
+    fn create_page(folder: &str, file_name: &str, title: &str) -> Page {
+        let content = format!("---\ntitle: {}\n---\nContent", title);
+        let mut page = Page::load_from_str(&content, Path::new(file_name)).unwrap();
+        page.set_folder(folder.to_string());
+        page
+    }
+
+    #[test]
+    fn test_get_folder() {
+        let root = Path::new("/blog/pages");
+        assert_eq!(Pages::get_folder(root, Path::new("/blog/pages/about.md")), "");
+        assert_eq!(Pages::get_folder(root, Path::new("/blog/pages/Docs/Guide/setup.md")), "docs/guide");
+    }
+
+    #[test]
+    fn test_get() {
+        let pages = Pages {
+            items: vec![create_page("", "posts.html", "Posts"), create_page("docs", "index.md", "Docs"), create_page("blog", "index.md", "Blog")]
+                .into_iter()
+                .map(Arc::new)
+                .collect(),
+        };
+
+        assert_eq!(
+            pages
+                .get("posts.html")
+                .map(|page| page.title.clone())
+                .as_deref(),
+            Some("Posts")
+        );
+        assert_eq!(
+            pages
+                .get("blog/index.md")
+                .map(|page| page.title.clone())
+                .as_deref(),
+            Some("Blog")
+        );
+        assert!(pages.get("index.md").is_none());
+    }
+
+    #[test]
+    fn test_link() {
+        let mut pages = vec![
+            create_page("", "about.md", "About"),
+            create_page("docs", "index.md", "Docs"),
+            create_page("docs", "setup.md", "Setup"),
+            create_page("docs", "install.md", "Install"),
+            create_page("docs/guide", "index.md", "Guide"),
+            create_page("docs/guide/advanced", "usage.md", "Usage"),
+        ];
+
+        Pages::link(&mut pages, false);
+
+        assert_eq!(pages[0].slug, "about");
+        assert_eq!(pages[0].parent, None);
+        assert_eq!(pages[1].slug, "docs/index");
+        assert_eq!(pages[1].parent, None);
+        assert_eq!(pages[1].children, vec!["docs/guide/index", "docs/install", "docs/setup"]);
+        assert_eq!(pages[2].parent.as_deref(), Some("docs/index"));
+        assert_eq!(pages[4].parent.as_deref(), Some("docs/index"));
+        assert_eq!(pages[4].children, vec!["docs/guide/advanced/usage"]);
+        assert_eq!(pages[5].parent.as_deref(), Some("docs/guide/index"));
+    }
+
+    #[test]
+    fn test_link_languages() {
+        let languages = vec![
+            Language {
+                code: "en".to_string(),
+                name: "English".to_string(),
+                prefix: "".to_string(),
+            },
+            Language {
+                code: "tr".to_string(),
+                name: "Türkçe".to_string(),
+                prefix: "/tr".to_string(),
+            },
+        ];
+
+        let create_page = |file_name: &str, title: &str| {
+            let content = format!("---\ntitle: {}\n---\nContent", title);
+            let mut page = Page::load_from_str(&content, Path::new(file_name)).unwrap();
+            page.set_language(&languages);
+            page.set_folder("docs".to_string());
+            page
+        };
+
+        let mut pages = vec![create_page("index.md", "Docs"), create_page("setup.md", "Setup"), create_page("setup.tr.md", "Kurulum")];
+        Pages::link(&mut pages, false);
+
+        assert_eq!(pages[2].slug, "docs/setup");
+        assert_eq!(pages[2].lang.as_deref(), Some("tr"));
+        assert_eq!(pages[2].url(), "/tr/docs/setup.html");
+        assert_eq!(pages[2].parent, None);
+        assert_eq!(pages[1].parent.as_deref(), Some("docs/index"));
+        assert_eq!(pages[0].children, vec!["docs/setup"]);
+    }
+}
@@ -7,13 +7,18 @@ use std::{
     sync::Arc,
 };
 
-use chrono::{DateTime, Utc};
-use minijinja::{value::Object, Value};
+use chrono::{DateTime, Datelike, Utc};
+use minijinja::{context, value::Object, Value};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    config::MarkdownOptions,
+    consts::PREVIEW_PATH,
+    context::get_context,
     error::Error,
+    language::{detect_language, split_language, Language},
     tools::{get_file_content, parse_yaml_front_matter},
+    wikilinks::backlinks_of,
 };
 const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
@@ -33,6 +38,10 @@ struct InnerPost {
     #[serde(default, with = "date_format")]
     pub date: DateTime<Utc>,
 
+    // Last update date of the post, posts without it are not marked as modified
+    #[serde(default, with = "optional_date_format")]
+    pub updated: Option<DateTime<Utc>>,
+
     #[serde(default)]
     pub slug: String,
 
@@ -42,6 +51,48 @@ struct InnerPost {
     #[serde(default)]
     pub draft: bool,
 
+    #[serde(default, alias = "template")]
+    pub layout: Option<String>,
+
+    #[serde(default)]
+    pub lang: Option<String>,
+
+    #[serde(default)]
+    pub translation_key: Option<String>,
+
+    #[serde(default)]
+    pub author: Option<String>,
+
+    #[serde(default)]
+    pub authors: Vec<String>,
+
+    #[serde(default)]
+    pub markdown: MarkdownOptions,
+
+    #[serde(default)]
+    pub description: Option<String>,
+
+    #[serde(default)]
+    pub cover: Option<String>,
+
+    #[serde(default)]
+    pub canonical: Option<String>,
+
+    #[serde(default)]
+    pub pinned: bool,
+
+    #[serde(default)]
+    pub weight: i64,
+
+    #[serde(default)]
+    pub unlisted: bool,
+
+    #[serde(default, skip)]
+    pub preview: Option<String>,
+
+    #[serde(default, skip)]
+    pub prefix: String,
+
     #[serde(default, skip)]
     pub path: PathBuf,
 }
@@ -70,6 +121,32 @@ pub mod date_format {
     }
 }
 
+pub mod optional_date_format {
+    use chrono::{DateTime, Utc};
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(date: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match date {
+            Some(date) => super::date_format::serialize(date, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Wrapper(#[serde(with = "super::date_format")] DateTime<Utc>);
+
+        let wrapper = Option::<Wrapper>::deserialize(deserializer)?;
+        Ok(wrapper.map(|Wrapper(date)| date))
+    }
+}
+
 impl Post {
     pub fn load_from_path(path: &PathBuf) -> Result<Self, Error> {
         let content: String = get_file_content(path)?;
@@ -110,6 +187,133 @@ impl Post {
         self.inner.read().draft
     }
 
+    pub fn author_ids(&self) -> Vec<String> {
+        let post = self.inner.read();
+        let mut authors = post.authors.clone();
+        if let Some(author) = &post.author {
+            if !authors.contains(author) {
+                authors.insert(0, author.clone());
+            }
+        }
+        authors
+    }
+
+    fn resolve_authors(&self) -> Vec<Value> {
+        let ctx = match get_context(snafu::location!()) {
+            Ok(ctx) => ctx,
+            Err(_) => return Vec::new(),
+        };
+
+        self.author_ids()
+            .iter()
+            .filter_map(|id| ctx.authors.get(id))
+            .map(|author| Value::from_dyn_object(author.clone()))
+            .collect()
+    }
+
+    pub fn description(&self) -> Option<String> {
+        self.inner.read().description.clone()
+    }
+
+    pub fn cover(&self) -> Option<String> {
+        self.inner.read().cover.clone()
+    }
+
+    pub fn canonical(&self) -> Option<String> {
+        self.inner.read().canonical.clone()
+    }
+
+    pub fn pinned(&self) -> bool {
+        self.inner.read().pinned
+    }
+
+    pub fn weight(&self) -> i64 {
+        self.inner.read().weight
+    }
+
+    pub fn unlisted(&self) -> bool {
+        self.inner.read().unlisted
+    }
+
+    pub fn preview(&self) -> bool {
+        self.inner.read().preview.is_some()
+    }
+
+    pub fn set_preview(&self, hash: String) {
+        self.inner.write().preview = Some(hash);
+    }
+
+    pub fn url(&self) -> String {
+        let post = self.inner.read();
+        match &post.preview {
+            Some(hash) => format!("{}/{}/{}/{}.html", post.prefix, PREVIEW_PATH, hash, post.slug),
+            None => format!("{}/{}/{}/{}/{}.html", post.prefix, post.date.year(), post.date.month(), post.date.day(), post.slug),
+        }
+    }
+
+    pub fn set_language(&self, languages: &[Language]) {
+        let mut post = self.inner.write();
+        let name = post
+            .path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+
+        let (lang, prefix) = detect_language(post.lang.as_deref(), &name, languages, &post.path.display().to_string());
+        if let Some((slug, _)) = split_language(&name, languages) {
+            if post.slug == name {
+                post.slug = slug.to_string();
+            }
+        }
+
+        post.lang = Some(lang);
+        post.prefix = prefix;
+    }
+
+    pub fn lang(&self) -> String {
+        self.inner.read().lang.clone().unwrap_or_default()
+    }
+
+    pub fn translation_key(&self) -> String {
+        let post = self.inner.read();
+        post.translation_key
+            .clone()
+            .unwrap_or_else(|| post.slug.clone())
+    }
+
+    fn translations(&self) -> Vec<Value> {
+        let ctx = match get_context(snafu::location!()) {
+            Ok(ctx) => ctx,
+            Err(_) => return Vec::new(),
+        };
+
+        let key = self.translation_key();
+        let posts: Vec<&Arc<Post>> = ctx
+            .posts
+            .posts
+            .iter()
+            .chain(ctx.posts.unlisted.iter())
+            .filter(|post| post.translation_key() == key)
+            .collect();
+
+        ctx.languages
+            .iter()
+            .filter_map(|language| {
+                let post = posts.iter().find(|post| post.lang() == language.code)?;
+                Some(context! { lang => language.code, hreflang => language.code, name => language.name, title => post.title().as_str(), url => Value::from_safe_string(post.url()) })
+            })
+            .collect()
+    }
+
+    pub fn markdown(&self) -> MarkdownOptions {
+        self.inner.read().markdown.clone()
+    }
+
+    pub fn layout(&self) -> Option<String> {
+        self.inner.read().layout.clone()
+    }
+
     pub fn path(&self) -> PathBuf {
         self.inner.read().path.clone()
     }
@@ -118,6 +322,10 @@ impl Post {
         self.inner.read().date
     }
 
+    pub fn updated(&self) -> Option<DateTime<Utc>> {
+        self.inner.read().updated
+    }
+
     pub fn tags(&self) -> Vec<String> {
         self.inner.read().tags.clone()
     }
@@ -130,15 +338,34 @@ impl Object for Post {
             "title" => Some(Value::from(self.title().as_str())),
             "content" => Some(Value::from(self.content().as_str())),
             "date" => Some(Value::from(self.date().format(DATE_FORMAT).to_string())),
+            "updated" => self
+                .updated()
+                .map(|date| Value::from(date.format(DATE_FORMAT).to_string())),
             "slug" => Some(Value::from(self.slug().as_str())),
             "tags" => Some(Value::from(self.tags())),
             "draft" => Some(Value::from(self.draft())),
+            "layout" => self.layout().map(Value::from),
+            "lang" => Some(Value::from(self.lang())),
+            "translation_key" => Some(Value::from(self.translation_key())),
+            "translations" => Some(Value::from(self.translations())),
+            "authors" => Some(Value::from(self.resolve_authors())),
+            "author" => self.resolve_authors().into_iter().next(),
+            "pinned" => Some(Value::from(self.pinned())),
+            "weight" => Some(Value::from(self.weight())),
+            "unlisted" => Some(Value::from(self.unlisted())),
+            "preview" => Some(Value::from(self.preview())),
+            "url" => Some(Value::from(self.url())),
+            "description" => self.description().map(Value::from),
+            "cover" => self.cover().map(Value::from),
+            "canonical" => self.canonical().map(Value::from),
+            "backlinks" => Some(backlinks_of(&self.url())),
             _ => None,
         }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
     use std::fs::File;
@@ -186,5 +413,71 @@ mod tests {
         assert_eq!(post.get_value(&Value::from("slug")).unwrap(), Value::from("test-post"));
         assert_eq!(post.get_value(&Value::from("tags")).unwrap(), Value::from(vec!["rust", "test"]));
         assert_eq!(post.get_value(&Value::from("draft")).unwrap(), Value::from(false));
+        assert_eq!(post.get_value(&Value::from("layout")), None);
+        assert_eq!(post.get_value(&Value::from("url")).unwrap(), Value::from("/2023/10/1/test-post.html"));
+    }
+
+    #[test]
+    fn test_preview_url() {
+        let content = "---\ntitle: Test Post\ndate: 2023-10-01 12:00:00\nslug: test-post\ndraft: true\n---\nThis is a test post.";
+        let post = Post::load_from_str(content, Path::new("test_post.md")).unwrap();
+        assert!(!post.preview());
+
+        post.set_preview("0123456789abcdef".to_string());
+        assert!(post.preview());
+        assert_eq!(post.url(), "/preview/0123456789abcdef/test-post.html");
+    }
+
+    #[test]
+    fn test_author_ids() {
+        let content = "---\ntitle: Test Post\ndate: 2023-10-01 12:00:00\nauthor: erhan\nauthors: [\"jane\", \"erhan\"]\n---\nThis is a test post.";
+        let post = Post::load_from_str(content, Path::new("test_post.md")).unwrap();
+        assert_eq!(post.author_ids(), vec!["jane", "erhan"]);
+
+        let content = "---\ntitle: Test Post\ndate: 2023-10-01 12:00:00\nauthor: erhan\n---\nThis is a test post.";
+        let post = Post::load_from_str(content, Path::new("test_post.md")).unwrap();
+        assert_eq!(post.author_ids(), vec!["erhan"]);
+    }
+
+    #[test]
+    fn test_set_language() {
+        let languages = vec![
+            Language {
+                code: "en".to_string(),
+                name: "English".to_string(),
+                prefix: "".to_string(),
+            },
+            Language {
+                code: "tr".to_string(),
+                name: "Türkçe".to_string(),
+                prefix: "/tr".to_string(),
+            },
+        ];
+
+        let content = "---\ntitle: Test Post\ndate: 2023-10-01 12:00:00\n---\nThis is a test post.";
+        let post = Post::load_from_str(content, Path::new("test_post.tr.md")).unwrap();
+        post.set_language(&languages);
+        assert_eq!(post.slug().as_str(), "test_post");
+        assert_eq!(post.lang(), "tr");
+        assert_eq!(post.translation_key(), "test_post");
+        assert_eq!(post.url(), "/tr/2023/10/1/test_post.html");
+
+        let content = "---\ntitle: Test Post\ndate: 2023-10-01 12:00:00\ntranslation_key: hello\n---\nThis is a test post.";
+        let post = Post::load_from_str(content, Path::new("test_post.md")).unwrap();
+        post.set_language(&languages);
+        assert_eq!(post.lang(), "en");
+        assert_eq!(post.translation_key(), "hello");
+        assert_eq!(post.url(), "/2023/10/1/test_post.html");
+    }
+
+    #[test]
+    fn test_layout() {
+        let content = "---\ntitle: Test Post\ndate: 2023-10-01 12:00:00\nlayout: landing.html\n---\nThis is a test post.";
+        let post = Post::load_from_str(content, Path::new("test_post.md")).unwrap();
+        assert_eq!(post.layout().as_deref(), Some("landing.html"));
+
+        let content = "---\ntitle: Test Post\ndate: 2023-10-01 12:00:00\ntemplate: special.html\n---\nThis is a test post.";
+        let post = Post::load_from_str(content, Path::new("test_post.md")).unwrap();
+        assert_eq!(post.layout().as_deref(), Some("special.html"));
     }
 }
@@ -1,54 +1,95 @@
 use std::sync::Arc;
 
-use chrono::Datelike;
 use minijinja::{
     value::{Enumerator, Object},
     Value,
 };
 use serde::{Deserialize, Serialize};
-use snafu::{OptionExt, ResultExt};
+use snafu::ResultExt;
 
 use crate::{
+    authors::Authors,
     consts::POST_HTML,
     context::{get_context, get_mut_context},
     document::{DocumentContext, DocumentType},
     engine::{RenderEngine, Renderable},
-    error::{FailedToAddTagSnafu, FolderCreationFailedSnafu},
+    error::FolderCreationFailedSnafu,
     post::Post,
-    tools::get_files,
+    tools::{get_files, preview_hash},
 };
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Posts {
     pub posts: Vec<Arc<Post>>,
+    pub unlisted: Vec<Arc<Post>>,
+    pub previews: Vec<Arc<Post>>,
 }
 
 impl Posts {
     pub fn load() -> crate::Result<Self> {
         let mut ctx = get_mut_context(snafu::location!())?;
         let mut posts = Vec::new();
+        let mut unlisted = Vec::new();
+        let mut previews = Vec::new();
+        let mut authors = Authors::new(&ctx.config.authors);
         let files = get_files(&ctx.posts_path, "md")?;
 
         for file in files {
             let post = Arc::new(Post::load_from_path(&file)?);
+            post.set_language(&ctx.languages);
 
             if !ctx.draft && post.draft() {
+                if let (true, Some(secret)) = (ctx.preview, &ctx.config.preview_secret) {
+                    let hash = preview_hash(&post.slug(), secret);
+                    post.set_preview(hash);
+                    previews.push(post);
+                }
                 continue;
             }
 
-            for tag in post.tags() {
-                ctx.tags
-                    .add(tag.clone(), post.clone())
-                    .context(FailedToAddTagSnafu { tag })?;
+            if post.unlisted() {
+                unlisted.push(post);
+                continue;
+            }
+
+            for id in post.author_ids() {
+                if authors.add(&id, post.clone()).is_none() {
+                    log::warn!("Author '{}' is not defined in the config, post: {}", id, file.display());
+                }
             }
 
             posts.push(post);
             // log::trace!("{}: {}", "Parsed", file.display());
         }
 
-        posts.sort_by_key(|b| std::cmp::Reverse(b.date()));
+        Self::sort(&mut posts);
+        Self::sort(&mut unlisted);
 
-        Ok(Self { posts })
+        authors.sort_posts();
+        ctx.authors = Arc::new(authors);
+
+        Ok(Self { posts, unlisted, previews })
+    }
+
+    // Pinned posts come first, then lower weights, then newer posts
+    pub fn sort(posts: &mut [Arc<Post>]) {
+        posts.sort_by_key(|post| (!post.pinned(), post.weight(), std::cmp::Reverse(post.date())));
+    }
+
+    pub fn filter(&self, lang: &str) -> Self {
+        let filter = |posts: &Vec<Arc<Post>>| -> Vec<Arc<Post>> {
+            posts
+                .iter()
+                .filter(|post| post.lang() == lang)
+                .cloned()
+                .collect()
+        };
+
+        Self {
+            posts: filter(&self.posts),
+            unlisted: filter(&self.unlisted),
+            previews: filter(&self.previews),
+        }
     }
 }
 
@@ -57,34 +98,42 @@ impl Renderable for Posts {
     fn render(&self, engine: &RenderEngine<'_>, _: Self::Context) -> crate::Result<()> {
         let general_ctx = get_context(snafu::location!())?;
 
-        for (index, post) in self.posts.iter().enumerate() {
-            if !general_ctx.draft && post.draft() {
-                return Ok(());
+        for (index, post) in self
+            .posts
+            .iter()
+            .chain(self.unlisted.iter())
+            .chain(self.previews.iter())
+            .enumerate()
+        {
+            if !general_ctx.draft && post.draft() && !post.preview() {
+                continue;
             }
 
             let source_path = post.path();
-            let date = post.date();
-            let target_folder = general_ctx
+            let target_file_path = general_ctx
                 .config
                 .deployment_folder
-                .join(date.year().to_string())
-                .join(date.month().to_string())
-                .join(date.day().to_string());
+                .join(post.url().trim_start_matches('/'));
 
-            std::fs::create_dir_all(&target_folder).context(FolderCreationFailedSnafu { path: target_folder.clone() })?;
+            if let Some(target_folder) = target_file_path.parent() {
+                std::fs::create_dir_all(target_folder).context(FolderCreationFailedSnafu { path: target_folder })?;
+            }
 
-            let target_file_path = target_folder.join(format!("{}.html", post.slug()));
+            let template = engine.get_layout(post.layout().as_deref(), POST_HTML, &source_path)?;
             let render_ctx = DocumentContext {
                 source_file_path: source_path.clone(),
                 target_file_path,
-                template: POST_HTML.to_string(),
+                template,
+                lang: post.lang(),
+                markdown: general_ctx.config.markdown.merge(&post.markdown()),
                 title: post.title().clone(),
                 index,
                 data: Value::from_dyn_object(post.clone()),
             };
 
             // Render the page
-            DocumentType::Markdown.render(engine, render_ctx)?;
+            let content = DocumentType::Markdown.render(engine, render_ctx)?;
+            engine.set_content(post.url(), content);
         }
 
         Ok(())
@@ -105,3 +154,30 @@ impl Object for Posts {
         Enumerator::Seq(self.posts.len())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn create_post(slug: &str, date: &str, extra: &str) -> Arc<Post> {
+        let content = format!("---\ntitle: {}\ndate: {}\n{}\n---\nContent", slug, date, extra);
+        Arc::new(Post::load_from_str(&content, Path::new(&format!("{}.md", slug))).unwrap())
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut posts = vec![
+            create_post("old", "2023-01-01 12:00:00", ""),
+            create_post("new", "2024-01-01 12:00:00", ""),
+            create_post("pinned", "2022-01-01 12:00:00", "pinned: true"),
+            create_post("heavy", "2025-01-01 12:00:00", "weight: 10"),
+            create_post("light", "2021-01-01 12:00:00", "weight: -1"),
+        ];
+
+        Posts::sort(&mut posts);
+        let slugs: Vec<String> = posts.iter().map(|post| post.slug().to_string()).collect();
+        assert_eq!(slugs, vec!["pinned", "light", "new", "old", "heavy"]);
+    }
+}
@@ -0,0 +1,216 @@
+use std::path::Path;
+
+use pulldown_cmark::{CowStr, Event, Tag};
+use snafu::OptionExt;
+
+use crate::{context::TimugContext, error::DocumentReferenceNotFoundSnafu, page::Page};
+
+const PATH_PREFIX: &str = "@/";
+const POST_PREFIX: &str = "@post:";
+const PAGE_PREFIX: &str = "@page:";
+
+pub fn is_reference(url: &str) -> bool {
+    url.starts_with(PATH_PREFIX) || url.starts_with(POST_PREFIX) || url.starts_with(PAGE_PREFIX)
+}
+
+// Theme pages are rendered under the prefix of the current language, custom pages have their own language
+pub fn page_url(ctx: &TimugContext, page: &Page, lang: &str) -> String {
+    match page.lang {
+        Some(_) => page.url(),
+        None => {
+            let prefix = ctx
+                .languages
+                .iter()
+                .find(|language| language.code == lang)
+                .map(|language| language.prefix.as_str())
+                .unwrap_or_default();
+            format!("{}{}", prefix, page.url())
+        }
+    }
+}
+
+// Same language version of the post is preferred, posts of the other languages are used if the post is not translated
+pub fn post_url(ctx: &TimugContext, slug: &str, lang: &str) -> Option<String> {
+    let mut posts = ctx
+        .posts
+        .posts
+        .iter()
+        .chain(ctx.posts.unlisted.iter())
+        .filter(|post| post.slug().as_str() == slug);
+    let first = posts.next()?;
+    let post = match first.lang() == lang {
+        true => first,
+        false => posts.find(|post| post.lang() == lang).unwrap_or(first),
+    };
+    Some(post.url())
+}
+
+// Source file of the post or the page, relative to the blog folder, preview posts and unpublished pages are missing documents
+fn document_by_path(ctx: &TimugContext, path: &str, lang: &str) -> Option<String> {
+    let path = ctx.config.blog_path.join(path);
+    if let Some(post) = ctx
+        .posts
+        .posts
+        .iter()
+        .chain(ctx.posts.unlisted.iter())
+        .find(|post| post.path() == path)
+    {
+        return Some(post.url());
+    }
+
+    ctx.pages
+        .items
+        .iter()
+        .filter(|page| page.render && (ctx.draft || !page.draft))
+        .find(|page| Path::new(&page.path) == path)
+        .map(|page| page_url(ctx, page, lang))
+}
+
+// Url of the "@/posts/hello.md", "@post:hello" or "@page:about" reference, anchors are kept
+pub fn resolve_reference(ctx: &TimugContext, reference: &str, lang: &str) -> Option<String> {
+    let (target, anchor) = match reference.split_once('#') {
+        Some((target, anchor)) => (target, format!("#{}", anchor)),
+        None => (reference, String::new()),
+    };
+
+    let url = if let Some(path) = target.strip_prefix(PATH_PREFIX) {
+        document_by_path(ctx, path, lang)
+    } else if let Some(slug) = target.strip_prefix(POST_PREFIX) {
+        post_url(ctx, slug, lang)
+    } else if let Some(slug) = target.strip_prefix(PAGE_PREFIX) {
+        ctx.pages
+            .find(slug, Some(lang))
+            .map(|page| page_url(ctx, &page, lang))
+    } else {
+        None
+    }?;
+
+    Some(format!("{}{}", url, anchor))
+}
+
+// Reference links are replaced with the document urls, missing documents fail the build
+pub fn reference_link_events<'a>(events: Vec<Event<'a>>, ctx: &TimugContext, lang: &str, document: &str) -> crate::Result<Vec<Event<'a>>> {
+    events
+        .into_iter()
+        .map(|event| match event {
+            Event::Start(Tag::Link { link_type, dest_url, title, id }) if is_reference(&dest_url) => {
+                let url = resolve_reference(ctx, &dest_url, lang).context(DocumentReferenceNotFoundSnafu { reference: dest_url.to_string(), document })?;
+                Ok(Event::Start(Tag::Link { link_type, dest_url: CowStr::from(url), title, id }))
+            }
+            event => Ok(event),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use pulldown_cmark::{Parser, Tag, TagEnd};
+
+    use super::*;
+    use crate::{error::Error, language::Language, pages::Pages, post::Post, posts::Posts};
+
+    fn create_context() -> TimugContext {
+        let mut ctx = TimugContext::default();
+        ctx.config.blog_path = PathBuf::from("/blog");
+        ctx.languages = vec![
+            Language {
+                code: "en".to_string(),
+                name: "English".to_string(),
+                prefix: String::new(),
+            },
+            Language {
+                code: "tr".to_string(),
+                name: "Türkçe".to_string(),
+                prefix: "/tr".to_string(),
+            },
+        ];
+
+        let mut posts = Posts::default();
+        for (file_name, content) in [
+            ("hello.md", "---\ntitle: Hello\ndate: 2024-10-18 12:00:00\n---\nHello"),
+            ("hello.tr.md", "---\ntitle: Merhaba\ndate: 2024-10-19 12:00:00\n---\nMerhaba"),
+            ("only.md", "---\ntitle: Only\ndate: 2024-10-20 12:00:00\n---\nOnly"),
+        ] {
+            let post = Post::load_from_str(content, &PathBuf::from("/blog/posts").join(file_name)).unwrap();
+            post.set_language(&ctx.languages);
+            posts.posts.push(Arc::new(post));
+        }
+
+        let preview = Post::load_from_str("---\ntitle: Preview\ndate: 2024-10-21 12:00:00\ndraft: true\n---\nPreview", &PathBuf::from("/blog/posts/preview.md")).unwrap();
+        preview.set_language(&ctx.languages);
+        posts.previews.push(Arc::new(preview));
+
+        let mut pages = Vec::new();
+        for (file_name, content) in [("about.md", "---\ntitle: About\nrender: true\n---\nAbout"), ("notes.md", "---\ntitle: Notes\nrender: true\ndraft: true\n---\nNotes")] {
+            let mut page = Page::load_from_str(content, &PathBuf::from("/blog/pages").join(file_name)).unwrap();
+            page.set_language(&ctx.languages);
+            pages.push(Arc::new(page));
+        }
+
+        ctx.posts = Arc::new(posts);
+        ctx.pages = Arc::new(Pages { items: pages });
+        ctx
+    }
+
+    #[test]
+    fn test_is_reference() {
+        assert!(is_reference("@/posts/hello.md"));
+        assert!(is_reference("@post:hello#intro"));
+        assert!(is_reference("@page:about"));
+        assert!(!is_reference("/2024/10/18/hello.html"));
+        assert!(!is_reference("mailto:@post:hello"));
+        assert!(!is_reference("@someone"));
+    }
+
+    #[test]
+    fn test_resolve_reference() {
+        let ctx = create_context();
+        assert_eq!(resolve_reference(&ctx, "@/posts/hello.tr.md", "en").as_deref(), Some("/tr/2024/10/19/hello.html"));
+        assert_eq!(resolve_reference(&ctx, "@post:hello#intro", "tr").as_deref(), Some("/tr/2024/10/19/hello.html#intro"));
+        assert_eq!(resolve_reference(&ctx, "@post:hello", "en").as_deref(), Some("/2024/10/18/hello.html"));
+        assert_eq!(resolve_reference(&ctx, "@post:only#end", "tr").as_deref(), Some("/2024/10/20/only.html#end"));
+        assert_eq!(resolve_reference(&ctx, "@page:about", "tr").as_deref(), Some("/about.html"));
+        assert_eq!(resolve_reference(&ctx, "@post:missing", "en"), None);
+    }
+
+    #[test]
+    fn test_unpublished_documents() {
+        let mut ctx = create_context();
+        assert_eq!(resolve_reference(&ctx, "@/pages/about.md#team", "en").as_deref(), Some("/about.html#team"));
+        assert_eq!(resolve_reference(&ctx, "@post:preview", "en"), None);
+        assert_eq!(resolve_reference(&ctx, "@/posts/preview.md", "en"), None);
+        assert_eq!(resolve_reference(&ctx, "@/pages/notes.md", "en"), None);
+
+        ctx.draft = true;
+        assert_eq!(resolve_reference(&ctx, "@/pages/notes.md", "en").as_deref(), Some("/notes.html"));
+    }
+
+    #[test]
+    fn test_reference_link_events() {
+        let ctx = create_context();
+        let events: Vec<Event> = Parser::new("[Hello](@post:hello) and [About](/about.html)").collect();
+        let events = reference_link_events(events, &ctx, "tr", "index.md").unwrap();
+        let urls: Vec<String> = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Start(Tag::Link { dest_url, .. }) => Some(dest_url.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(urls, vec!["/tr/2024/10/19/hello.html", "/about.html"]);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, Event::End(TagEnd::Link))));
+
+        let events: Vec<Event> = Parser::new("[Missing](@post:missing#intro)").collect();
+        match reference_link_events(events, &ctx, "en", "index.md") {
+            Err(Error::DocumentReferenceNotFound { reference, document, .. }) => {
+                assert_eq!(reference, "@post:missing#intro");
+                assert_eq!(document, "index.md");
+            }
+            result => panic!("Unexpected result: {:?}", result.map(|events| events.len())),
+        }
+    }
+}
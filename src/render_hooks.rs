@@ -0,0 +1,253 @@
+use minijinja::{context, Environment, Value};
+use pulldown_cmark::{CowStr, Event, LinkType, Tag, TagEnd};
+use snafu::ResultExt;
+
+use crate::{
+    context::get_context,
+    error::RenderHookFailedSnafu,
+    images::{ImageProcessor, ResponsiveImage},
+    tools::get_slug,
+};
+
+pub const LINK_HOOK: &str = "_render_link.html";
+pub const IMAGE_HOOK: &str = "_render_image.html";
+pub const HEADING_HOOK: &str = "_render_heading.html";
+pub const BLOCKQUOTE_HOOK: &str = "_render_blockquote.html";
+
+// Default image output when the responsive images are enabled and the theme has no image hook
+static IMAGE_HTML: &str = include_str!("render_image.html");
+
+// Theme templates that replace the default html output of the markdown elements, loaded once for every rendered content
+pub struct RenderHooks<'a> {
+    env: Environment<'a>,
+    document: &'a str,
+    images: Option<ImageProcessor>,
+}
+
+impl<'a> RenderHooks<'a> {
+    pub fn load(document: &'a str) -> crate::Result<Self> {
+        let ctx = get_context(snafu::location!())?;
+        let mut env = Environment::new();
+
+        for name in [LINK_HOOK, IMAGE_HOOK, HEADING_HOOK, BLOCKQUOTE_HOOK] {
+            if let Some(page) = ctx.get_template_page(name) {
+                env.add_template_owned(name, page.content.clone())
+                    .context(RenderHookFailedSnafu { template: name, document })?;
+            }
+        }
+
+        let images = ImageProcessor::new(&ctx);
+        if images.is_some() && ctx.get_template_page(IMAGE_HOOK).is_none() {
+            env.add_template(IMAGE_HOOK, IMAGE_HTML)
+                .context(RenderHookFailedSnafu { template: IMAGE_HOOK, document })?;
+        }
+
+        Ok(Self { env, document, images })
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.env.get_template(name).is_ok()
+    }
+
+    fn render(&self, name: &str, ctx: Value) -> crate::Result<String> {
+        self.env
+            .get_template(name)
+            .and_then(|template| template.render(ctx))
+            .context(RenderHookFailedSnafu { template: name, document: self.document })
+    }
+
+    fn hook(&self, tag: &Tag<'_>) -> Option<&'static str> {
+        let name = match tag {
+            Tag::Link { .. } => LINK_HOOK,
+            Tag::Image { .. } => IMAGE_HOOK,
+            Tag::Heading { .. } => HEADING_HOOK,
+            Tag::BlockQuote(_) => BLOCKQUOTE_HOOK,
+            _ => return None,
+        };
+
+        self.has(name).then_some(name)
+    }
+
+    // Elements with a hook template are replaced with the rendered template, their content is rendered first
+    pub fn apply<'e>(&self, events: Vec<Event<'e>>) -> crate::Result<Vec<Event<'e>>> {
+        let mut result = Vec::with_capacity(events.len());
+        let mut events = events.into_iter();
+
+        while let Some(event) = events.next() {
+            let (tag, name) = match event {
+                Event::Start(tag) => match self.hook(&tag) {
+                    Some(name) => (tag, name),
+                    None => {
+                        result.push(Event::Start(tag));
+                        continue;
+                    }
+                },
+                event => {
+                    result.push(event);
+                    continue;
+                }
+            };
+
+            // Links and blockquotes can be nested, so the matching end tag is searched
+            let mut inner = Vec::new();
+            let mut depth = 0;
+            let end_tag = std::mem::discriminant(&tag.to_end());
+            for event in events.by_ref() {
+                match &event {
+                    Event::Start(start) if std::mem::discriminant(start) == std::mem::discriminant(&tag) => depth += 1,
+                    Event::End(end) if std::mem::discriminant(end) == end_tag && depth == 0 => break,
+                    Event::End(end) if std::mem::discriminant(end) == end_tag => depth -= 1,
+                    _ => (),
+                }
+                inner.push(event);
+            }
+
+            let text = plain_text(&inner);
+            let mut content = String::new();
+            pulldown_cmark::html::push_html(&mut content, self.apply(inner)?.into_iter());
+            let content = Value::from_safe_string(content);
+
+            let html = match tag {
+                Tag::Link { link_type, dest_url, title, id } => self.render(
+                    name,
+                    context! {
+                        url => safe_url(&dest_url),
+                        title => title.as_ref(),
+                        id => id.as_ref(),
+                        text => text,
+                        content => content,
+                        external => is_external(&dest_url),
+                        email => link_type == LinkType::Email,
+                    },
+                )?,
+                Tag::Image { dest_url, title, id, .. } => {
+                    let responsive = match &self.images {
+                        Some(images) => images.process(&dest_url)?,
+                        None => None,
+                    };
+
+                    self.render(
+                        name,
+                        context! {
+                            url => safe_url(&dest_url),
+                            title => title.as_ref(),
+                            id => id.as_ref(),
+                            alt => text,
+                            external => is_external(&dest_url),
+                            lazy => self.images.as_ref().map(|images| images.config.lazy).unwrap_or_default(),
+                            sizes => self.images.as_ref().map(|images| images.config.sizes.clone()),
+                            ..responsive_context(responsive)
+                        },
+                    )?
+                }
+                Tag::Heading { level, id, classes, attrs } => self.render(
+                    name,
+                    context! {
+                        level => level as usize,
+                        id => id.map(|id| id.to_string()).unwrap_or_else(|| get_slug(&text)),
+                        classes => classes.iter().map(|class| class.to_string()).collect::<Vec<_>>(),
+                        attrs => attrs.iter().map(|(key, value)| (key.to_string(), value.as_ref().map(|value| value.to_string()))).collect::<Vec<_>>(),
+                        text => text,
+                        content => content,
+                    },
+                )?,
+                Tag::BlockQuote(kind) => self.render(
+                    name,
+                    context! {
+                        kind => kind.map(|kind| format!("{:?}", kind).to_lowercase()),
+                        content => content,
+                    },
+                )?,
+                _ => unreachable!(),
+            };
+
+            result.push(match name {
+                LINK_HOOK | IMAGE_HOOK => Event::InlineHtml(CowStr::from(html)),
+                _ => Event::Html(CowStr::from(html)),
+            });
+        }
+
+        Ok(result)
+    }
+}
+
+// Renders the events into html, hook templates of the theme are used when they exist
+pub fn push_html(events: Vec<Event<'_>>, hooks: &RenderHooks<'_>) -> crate::Result<String> {
+    warn_missing_alt(&events, hooks.document);
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, hooks.apply(events)?.into_iter());
+    Ok(html)
+}
+
+pub fn plain_text(events: &[Event<'_>]) -> String {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Text(text) | Event::Code(text) => Some(text.as_ref()),
+            Event::SoftBreak | Event::HardBreak => Some(" "),
+            _ => None,
+        })
+        .collect()
+}
+
+fn responsive_context(image: Option<ResponsiveImage>) -> Value {
+    match image {
+        Some(image) => context! {
+            width => image.width,
+            height => image.height,
+            srcset => safe_url(&image.srcset),
+            sources => image.sources.iter().map(|source| context! { type => safe_url(source.mime), srcset => safe_url(&source.srcset) }).collect::<Vec<_>>(),
+        },
+        None => context! {},
+    }
+}
+
+// Template auto escaping also escapes the slashes, so only the characters that break the attribute are escaped
+fn safe_url(url: &str) -> Value {
+    Value::from_safe_string(escape_url(url))
+}
+
+pub fn escape_url(url: &str) -> String {
+    url.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Images without alt text are not accessible, so they are reported while building
+fn warn_missing_alt(events: &[Event<'_>], document: &str) {
+    for window in events.windows(2) {
+        if let [Event::Start(Tag::Image { dest_url, .. }), Event::End(TagEnd::Image)] = window {
+            log::warn!("Image has no alt text: {}, document: {}", dest_url, document);
+        }
+    }
+}
+
+fn is_external(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://") || url.starts_with("//")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Options, Parser};
+
+    #[test]
+    fn test_apply() {
+        let mut env = Environment::new();
+        env.add_template(LINK_HOOK, "<a href=\"{{ url }}\"{% if external %} target=\"_blank\"{% endif %}>{{ content }}</a>")
+            .unwrap();
+        env.add_template(HEADING_HOOK, "<h{{ level }} id=\"{{ id }}\">{{ content }}<a href=\"#{{ id }}\">#</a></h{{ level }}>")
+            .unwrap();
+        let hooks = RenderHooks { env, document: "test.md", images: None };
+
+        let events: Vec<_> = Parser::new_ext("## Hello *World*\n\n[home](/index.html) [site](https://example.com)\n\n![alt](image.png)\n", Options::empty()).collect();
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, hooks.apply(events).unwrap().into_iter());
+
+        assert!(html.contains("<h2 id=\"hello-world\">Hello <em>World</em><a href=\"#hello-world\">#</a></h2>"));
+        assert!(html.contains("<a href=\"/index.html\">home</a>"));
+        assert!(html.contains("<a href=\"https://example.com\" target=\"_blank\">site</a>"));
+        assert!(html.contains("<img src=\"image.png\" alt=\"alt\" />"));
+    }
+}
@@ -0,0 +1,240 @@
+use std::{collections::HashSet, path::PathBuf, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use console::style;
+use pulldown_cmark::{utils::TextMergeStream, Event, Tag, TagEnd};
+use serde_json::{Map, Value};
+use snafu::ResultExt;
+
+use crate::{
+    config::{MarkdownOptions, SearchCollection, SearchConfig, SearchField},
+    context::get_context,
+    engine::{RenderEngine, Renderable},
+    error::JsonSerializationFailedSnafu,
+    page::Page,
+    post::Post,
+    tools::{get_file_name, parse_markdown},
+    wikilinks::{split_wiki_links, Segment},
+};
+
+// Shorter words are not useful for searching and only make the index bigger
+const MIN_TOKEN_LENGTH: usize = 2;
+
+pub struct SearchEntry<'a> {
+    pub title: &'a str,
+    pub url: &'a str,
+    pub tags: &'a [String],
+    pub date: Option<DateTime<Utc>>,
+    pub content: &'a str,
+    pub markdown: &'a MarkdownOptions,
+}
+
+impl SearchEntry<'_> {
+    // Only the configured fields are written, so the index stays small
+    pub fn to_json(&self, config: &SearchConfig) -> Map<String, Value> {
+        let text = plain_text(self.content, self.markdown);
+        let mut entry = Map::new();
+
+        for field in config.fields.iter() {
+            match field {
+                SearchField::Title => entry.insert("title".to_string(), Value::from(self.title)),
+                SearchField::Url => entry.insert("url".to_string(), Value::from(self.url)),
+                SearchField::Tags if !self.tags.is_empty() => entry.insert("tags".to_string(), Value::from(self.tags)),
+                SearchField::Date => match self.date {
+                    Some(date) => entry.insert("date".to_string(), Value::from(date.format("%Y-%m-%d").to_string())),
+                    None => None,
+                },
+                SearchField::Summary => entry.insert("summary".to_string(), Value::from(summary(&text, config.summary_length))),
+                SearchField::Body => entry.insert("body".to_string(), Value::from(tokenize(&text).join(" "))),
+                SearchField::Tags => None,
+            };
+        }
+
+        entry
+    }
+}
+
+// Template calls and variables are not a part of the document text
+fn strip_template_tags(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find('{') {
+        let close = match rest[start..].chars().nth(1) {
+            Some('%') => "%}",
+            Some('{') => "}}",
+            Some('#') => "#}",
+            _ => {
+                result.push_str(&rest[..start + 1]);
+                rest = &rest[start + 1..];
+                continue;
+            }
+        };
+
+        result.push_str(&rest[..start]);
+        match rest[start + 2..].find(close) {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+        result.push(' ');
+    }
+
+    result.push_str(rest);
+    result
+}
+
+// Text of the markdown document without the html and template tags
+pub fn plain_text(content: &str, options: &MarkdownOptions) -> String {
+    let content = strip_template_tags(content);
+    let mut text = String::new();
+    let mut in_code_block = false;
+
+    for event in TextMergeStream::new(parse_markdown(&content, options)) {
+        match event {
+            // Code blocks are not indexed, inline codes are kept as text
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::Text(_) if in_code_block => (),
+            Event::Text(part) => {
+                for segment in split_wiki_links(&part) {
+                    match segment {
+                        Segment::Text(part) => text.push_str(part),
+                        Segment::Link(link) => text.push_str(link.label.as_deref().unwrap_or(&link.target)),
+                    }
+                }
+            }
+            Event::Code(part) | Event::InlineMath(part) | Event::DisplayMath(part) => text.push_str(&part),
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            Event::End(end) => {
+                in_code_block &= end != TagEnd::CodeBlock;
+                text.push(' ');
+            }
+            _ => (),
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// First characters of the text, cut at a word boundary
+pub fn summary(text: &str, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+
+    let cut: String = text.chars().take(length).collect();
+    let cut = match cut.rfind(' ') {
+        Some(index) => &cut[..index],
+        None => cut.as_str(),
+    };
+    format!("{}…", cut.trim_end_matches(|ch: char| !ch.is_alphanumeric()))
+}
+
+// Unique lowercase words of the text, in the order of their first appearance
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    text.to_lowercase()
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|token| token.chars().count() >= MIN_TOKEN_LENGTH)
+        .filter(|token| seen.insert(token.to_string()))
+        .map(|token| token.to_string())
+        .collect()
+}
+
+pub struct SearchIndex {
+    pub config: SearchConfig,
+    pub posts: Vec<Arc<Post>>,
+    pub pages: Vec<Arc<Page>>,
+}
+
+impl Renderable for SearchIndex {
+    type Context = PathBuf;
+    fn render(&self, engine: &RenderEngine<'_>, file_name: PathBuf) -> crate::Result<()> {
+        let ctx = get_context(snafu::location!())?;
+        engine.update_status(style("Rendering search index").bold().cyan().to_string(), get_file_name(&file_name)?.as_str());
+
+        let mut entries = Vec::new();
+        for collection in self.config.collections.iter() {
+            match collection {
+                SearchCollection::Posts => {
+                    for post in self.posts.iter() {
+                        let markdown = ctx.config.markdown.merge(&post.markdown());
+                        let entry = SearchEntry {
+                            title: &post.title(),
+                            url: &post.url(),
+                            tags: &post.tags(),
+                            date: Some(post.date()),
+                            content: &post.content(),
+                            markdown: &markdown,
+                        };
+                        entries.push(entry.to_json(&self.config));
+                    }
+                }
+                SearchCollection::Pages => {
+                    for page in self.pages.iter() {
+                        let markdown = ctx.config.markdown.merge(&page.markdown);
+                        let entry = SearchEntry {
+                            title: &page.title,
+                            url: &page.url(),
+                            tags: &[],
+                            date: None,
+                            content: &page.content,
+                            markdown: &markdown,
+                        };
+                        entries.push(entry.to_json(&self.config));
+                    }
+                }
+            }
+        }
+
+        let content = serde_json::to_string(&entries).context(JsonSerializationFailedSnafu)?;
+        engine.write(content, &file_name)?;
+        engine.update_status(style("Generated search index").bold().green().to_string(), get_file_name(&file_name)?.as_str());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        let content = "# Hello\n\nSome *text* with <b>html</b>, [[notes|my notes]] and `code`.\n\n```rust\nfn main() {}\n```\n\n{% call alertbox(\"info\") %}\nInside the box\n{% endcall %}\n\n{{ gist(\"id\") }}";
+        assert_eq!(plain_text(content, &MarkdownOptions::default()), "Hello Some text with html, my notes and code. Inside the box");
+        assert_eq!(strip_template_tags("a {b} {{ c }} {# d"), "a {b}   ");
+    }
+
+    #[test]
+    fn test_to_json() {
+        let tags = vec!["rust".to_string()];
+        let entry = SearchEntry {
+            title: "Hello",
+            url: "/2024/1/2/hello.html",
+            tags: &tags,
+            date: Some(
+                DateTime::parse_from_rfc3339("2024-01-02T10:00:00Z")
+                    .unwrap()
+                    .into(),
+            ),
+            content: "Rust is fast. Rust is safe, and a bit of Ünicode.",
+            markdown: &MarkdownOptions::default(),
+        };
+
+        let json = entry.to_json(&SearchConfig::default());
+        assert_eq!(json["date"], "2024-01-02");
+        assert_eq!(json["tags"], serde_json::json!(["rust"]));
+        assert_eq!(json["body"], "rust is fast safe and bit of ünicode");
+
+        let config = SearchConfig {
+            fields: vec![SearchField::Title, SearchField::Summary],
+            summary_length: 20,
+            ..Default::default()
+        };
+        let json = entry.to_json(&config);
+        assert_eq!(json.keys().collect::<Vec<_>>(), vec!["summary", "title"]);
+        assert_eq!(json["summary"], "Rust is fast. Rust…");
+    }
+}
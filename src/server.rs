@@ -44,6 +44,7 @@ pub fn start_webserver(port: Option<u16>) -> crate::Result<()> {
 fn change_watcher() -> crate::Result<()> {
     let ctx = get_context(snafu::location!())?;
     let deployment_folder = ctx.config.deployment_folder.clone();
+    let cache_folder = ctx.cache_path.clone();
     let blog_path = ctx.config.blog_path.clone();
     let git_folder = ctx.git_folder.as_ref().map(|path| path.join(".git"));
     drop(ctx);
@@ -64,8 +65,9 @@ fn change_watcher() -> crate::Result<()> {
                     .map(|git_path| path.starts_with(git_path))
                     .unwrap_or_default();
                 let is_deployment_folder = path.starts_with(&deployment_folder);
+                let is_cache_folder = path.starts_with(&cache_folder);
 
-                if !is_deployment_folder && !is_git_folder {
+                if !is_deployment_folder && !is_git_folder && !is_cache_folder {
                     log::info!("{} has been updated", style(path.display()).yellow());
 
                     if need_rebuilding {
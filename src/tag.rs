@@ -23,25 +23,39 @@ pub struct Tag {
 pub struct TagContext {
     pub folder: PathBuf,
     pub index: usize,
+    pub lang: String,
+    pub prefix: String,
     pub template_path: String,
 }
 
+impl Tag {
+    // File name of the tag page without the extension
+    pub fn slug(&self) -> String {
+        unidecode(&self.name)
+            .replace([' ', '\r', '\n', '\t'], "-")
+            .to_lowercase()
+    }
+
+    pub fn url(&self) -> String {
+        format!("/tags/{}.html", self.slug())
+    }
+}
+
 impl Renderable for Tag {
     type Context = TagContext;
     fn render(&self, engine: &RenderEngine<'_>, ctx: TagContext) -> crate::Result<()> {
-        let name = unidecode(&self.name).replace([' ', '\r', '\n', '\t'], "-");
-        let context = engine.create_context()?;
-        let file_name = ctx.folder.join(format!("{}.html", name.to_lowercase()));
+        let context = engine.create_context(&ctx.lang)?;
+        let file_name = ctx.folder.join(format!("{}.html", self.slug()));
         engine.update_status(style("Rendering tag").bold().cyan().to_string(), get_file_name(&file_name)?.as_str());
 
-        let posts = Value::from_object(Posts { posts: self.posts.clone() });
+        let posts = Value::from_object(Posts { posts: self.posts.clone(), ..Default::default() });
 
         let template = engine
             .env
             .get_template(&ctx.template_path)
             .context(MarkdownTemplateNotFoundSnafu { template: &ctx.template_path })?;
         let context = context! {
-            ..context! { index => ctx.index, posts => posts },
+            ..context! { index => ctx.index, posts => posts, current_url => format!("{}{}", ctx.prefix, self.url()) },
             ..context.clone()
         };
 
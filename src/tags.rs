@@ -52,11 +52,7 @@ impl Tags {
         Some(())
     }
 
-    pub fn clear(&mut self) {
-        self.tags.clear();
-    }
-
-    pub fn iter(&self) -> TagsIterator {
+    pub fn iter(&self) -> TagsIterator<'_> {
         TagsIterator { tags: self, index: 0 }
     }
 }
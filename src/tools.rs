@@ -3,14 +3,22 @@ use std::{
     time::Instant,
 };
 
-use pulldown_cmark::{Options, Parser};
+use minijinja::Value;
+use pulldown_cmark::Parser;
 use snafu::prelude::*;
 use unidecode::unidecode;
 
 use crate::{
+    config::MarkdownOptions,
     consts::SPARKLE,
+    context::get_context,
     engine::create_engine,
     error::{CouldNotConvertOsStrSnafu, CouldNotGetFilenameSnafu, FileNotFoundSnafu, ReadingDirectoryFailedSnafu, Result},
+    extensions::{alertbox::AlertBox, codeblock::Codeblock, figure::Figure},
+    math::render_math,
+    references::reference_link_events,
+    render_hooks::{self, RenderHooks},
+    wikilinks::wiki_link_events,
 };
 
 pub fn get_file_name(path: &Path) -> Result<String> {
@@ -37,7 +45,7 @@ pub fn get_files(path: &PathBuf, extension: &str) -> Result<Vec<PathBuf>> {
         .filter_map(|path| {
             if path
                 .extension()
-                .map_or(false, |ext| ext.to_ascii_lowercase() == extension)
+                .is_some_and(|ext| ext.to_ascii_lowercase() == extension)
             {
                 Some(path)
             } else {
@@ -49,20 +57,64 @@ pub fn get_files(path: &PathBuf, extension: &str) -> Result<Vec<PathBuf>> {
     Ok(paths)
 }
 
+pub fn get_files_recursive(path: &PathBuf, extension: &str) -> Result<Vec<PathBuf>> {
+    let mut paths = get_files(path, extension)?;
+    let folders = std::fs::read_dir(path)
+        .context(ReadingDirectoryFailedSnafu { path })?
+        .flatten()
+        .map(|dir_entry| dir_entry.path())
+        .filter(|path| path.is_dir());
+
+    for folder in folders {
+        paths.extend(get_files_recursive(&folder, extension)?);
+    }
+
+    Ok(paths)
+}
+
 pub fn get_file_content(path: &PathBuf) -> Result<String> {
     let content = std::fs::read_to_string(path).context(FileNotFoundSnafu { path })?;
     Ok(content)
 }
 
-pub fn parse_yaml(content: &'_ str) -> Parser<'_> {
-    let mut opts = Options::empty();
-    opts.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
-    opts.insert(Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS);
-    opts.insert(Options::ENABLE_HEADING_ATTRIBUTES);
-    opts.insert(Options::ENABLE_TABLES);
-    opts.insert(Options::ENABLE_FOOTNOTES);
+pub fn parse_markdown<'a>(content: &'a str, options: &MarkdownOptions) -> Parser<'a> {
+    pulldown_cmark::Parser::new_ext(content, options.options())
+}
+
+// Every markdown content is rendered with the same pipeline, documents and the extensions such as alertbox and info
+pub fn render_markdown(content: &str, options: &MarkdownOptions, lang: &str, document: &str, after_bodies: &Value) -> Result<String> {
+    let events = {
+        let ctx = get_context(snafu::location!())?;
+        let events = wiki_link_events(parse_markdown(content, options), &ctx, lang, document);
+        reference_link_events(events, &ctx, lang, document)?
+    };
+    let events = match options.math() {
+        true => render_math(events.into_iter(), document)?,
+        false => events,
+    };
+    let events = Codeblock::highlight_events(events.into_iter(), lang, after_bodies)?;
 
-    pulldown_cmark::Parser::new_ext(content, opts)
+    // Figures and callouts render their content with the same hooks
+    let hooks = RenderHooks::load(document)?;
+    let events = match options.figures() {
+        true => Figure::figure_events(events, &hooks)?,
+        false => events,
+    };
+    let events = match options.callouts() {
+        true => AlertBox::callout_events(events, lang, &hooks)?,
+        false => events,
+    };
+
+    render_hooks::push_html(events, &hooks)
+}
+
+pub fn preview_hash(slug: &str, secret: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(format!("{}:{}", secret, slug).as_bytes());
+    digest[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
 }
 
 pub fn url_encode(url: String) -> String {
@@ -86,7 +138,7 @@ pub fn parse_yaml_front_matter(content: &'_ str) -> FrontMatterInfo<'_> {
     let mut content_start_position = 0;
     let mut start = 0;
 
-    for (index, ch) in content.chars().enumerate() {
+    for (index, ch) in content.char_indices() {
         if ch == '\n' {
             if content[start..index].trim() == "---" && front_matter_started {
                 front_matter_end_position = start;
@@ -131,6 +183,7 @@ pub fn get_slug(title: &str) -> String {
 }
 
 #[cfg(test)]
+#[allow(clippy::len_zero)]
 mod tests {
     use super::*;
     use std::fs::File;
@@ -162,6 +215,20 @@ mod tests {
         assert_eq!(files[0], file_path);
     }
 
+    #[test]
+    fn test_get_files_recursive() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("docs").join("guide")).unwrap();
+        File::create(dir.path().join("about.md")).unwrap();
+        File::create(dir.path().join("docs").join("index.md")).unwrap();
+        File::create(dir.path().join("docs").join("guide").join("setup.md")).unwrap();
+        File::create(dir.path().join("docs").join("logo.png")).unwrap();
+
+        let mut files = get_files_recursive(&dir.path().to_path_buf(), "md").unwrap();
+        files.sort();
+        assert_eq!(files, vec![dir.path().join("about.md"), dir.path().join("docs").join("guide").join("setup.md"), dir.path().join("docs").join("index.md")]);
+    }
+
     #[test]
     fn test_get_file_content() {
         let dir = tempdir().unwrap();
@@ -176,11 +243,20 @@ mod tests {
     #[test]
     fn test_parse_yaml() {
         let content = "---\nkey: value\n---\n# Heading\n";
-        let parser = parse_yaml(content);
+        let parser = parse_markdown(content, &MarkdownOptions::default());
         let events: Vec<_> = parser.collect();
         assert!(events.len() > 0);
     }
 
+    #[test]
+    fn test_preview_hash() {
+        let hash = preview_hash("hello-world", "secret");
+        assert_eq!(hash.len(), 32);
+        assert_eq!(hash, preview_hash("hello-world", "secret"));
+        assert_ne!(hash, preview_hash("hello-world", "another-secret"));
+        assert_ne!(hash, preview_hash("another-post", "secret"));
+    }
+
     #[test]
     fn test_yaml_front_matter() {
         let content = "---\nkey: value\n---\n# Heading\n";
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use minijinja::{context, Value};
+use pulldown_cmark::{utils::TextMergeStream, CowStr, Event, LinkType, Tag, TagEnd};
+
+use crate::{
+    config::MarkdownOptions,
+    context::{get_context, TimugContext},
+    tools::parse_markdown,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiLink {
+    pub target: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Link(WikiLink),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backlink {
+    pub title: String,
+    pub url: String,
+}
+
+impl Backlink {
+    pub fn to_value(&self) -> Value {
+        context! { title => self.title, url => Value::from_safe_string(self.url.clone()) }
+    }
+}
+
+// "[[target]]" and "[[target|label]]" parts of the text, brackets and new lines are not allowed in the link
+pub fn split_wiki_links(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("[[") {
+        let Some(length) = rest[start + 2..].find("]]") else {
+            break;
+        };
+
+        let inner = &rest[start + 2..start + 2 + length];
+        if inner.trim().is_empty() || inner.contains(['[', ']', '\n']) {
+            segments.push(Segment::Text(&rest[..start + 2]));
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        if start > 0 {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+
+        let (target, label) = match inner.split_once('|') {
+            Some((target, label)) => (target.trim(), Some(label.trim().to_string())),
+            None => (inner.trim(), None),
+        };
+        segments.push(Segment::Link(WikiLink { target: target.to_string(), label }));
+        rest = &rest[start + 4 + length..];
+    }
+
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+
+    segments
+}
+
+// Slug or title of a post or a page, the document in the same language is preferred
+pub fn resolve(ctx: &TimugContext, target: &str, lang: &str) -> Option<Backlink> {
+    let (target, anchor) = match target.split_once('#') {
+        Some((target, anchor)) => (target.trim(), format!("#{}", anchor.trim())),
+        None => (target, String::new()),
+    };
+
+    let mut candidates: Vec<(String, Backlink)> = ctx
+        .posts
+        .posts
+        .iter()
+        .chain(ctx.posts.unlisted.iter())
+        .filter(|post| *post.slug() == target || post.title().eq_ignore_ascii_case(target))
+        .map(|post| (post.lang(), Backlink { title: post.title().clone(), url: post.url() }))
+        .collect();
+
+    candidates.extend(
+        ctx.pages
+            .items
+            .iter()
+            .filter(|page| page.render && (ctx.draft || !page.draft) && (page.slug == target || page.title.eq_ignore_ascii_case(target)))
+            .map(|page| (page.lang.clone().unwrap_or_default(), Backlink { title: page.title.clone(), url: page.url() })),
+    );
+
+    let index = candidates
+        .iter()
+        .position(|(code, _)| code == lang)
+        .unwrap_or_default();
+    let mut link = (!candidates.is_empty()).then(|| candidates.swap_remove(index).1)?;
+    link.url.push_str(&anchor);
+    Some(link)
+}
+
+// Documents that link to the url, used for the "backlinks" values of the posts and pages
+pub fn backlinks_of(url: &str) -> Value {
+    match get_context(snafu::location!()) {
+        Ok(ctx) => Value::from(
+            ctx.backlinks
+                .get(url)
+                .map(|links| links.iter().map(Backlink::to_value).collect::<Vec<_>>())
+                .unwrap_or_default(),
+        ),
+        Err(_) => Value::from(Vec::<Value>::new()),
+    }
+}
+
+// Wiki links in the text are converted into markdown links, code blocks and inline codes are not changed
+pub fn wiki_link_events<'a>(events: impl Iterator<Item = Event<'a>>, ctx: &TimugContext, lang: &str, document: &str) -> Vec<Event<'a>> {
+    let mut result = Vec::new();
+    let mut in_code_block = false;
+
+    for event in TextMergeStream::new(events) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                in_code_block = true;
+                result.push(Event::Start(Tag::CodeBlock(kind)));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                result.push(Event::End(TagEnd::CodeBlock));
+            }
+            Event::Text(text) if !in_code_block && text.contains("[[") => {
+                for segment in split_wiki_links(&text) {
+                    match segment {
+                        Segment::Text(text) => result.push(Event::Text(CowStr::from(text.to_string()))),
+                        Segment::Link(link) => match resolve(ctx, &link.target, lang) {
+                            Some(target) => {
+                                result.push(Event::Start(Tag::Link {
+                                    link_type: LinkType::Inline,
+                                    dest_url: CowStr::from(target.url),
+                                    title: CowStr::from(""),
+                                    id: CowStr::from(""),
+                                }));
+                                result.push(Event::Text(CowStr::from(link.label.unwrap_or(target.title))));
+                                result.push(Event::End(TagEnd::Link));
+                            }
+                            None => {
+                                log::warn!("Wiki link target not found: {}, document: {}", link.target, document);
+                                result.push(Event::Text(CowStr::from(link.label.unwrap_or(link.target))));
+                            }
+                        },
+                    }
+                }
+            }
+            event => result.push(event),
+        }
+    }
+
+    result
+}
+
+fn wiki_link_targets(content: &str, options: &MarkdownOptions) -> Vec<String> {
+    let mut targets = Vec::new();
+    let mut in_code_block = false;
+
+    for event in TextMergeStream::new(parse_markdown(content, options)) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => in_code_block = false,
+            Event::Text(text) if !in_code_block => targets.extend(
+                split_wiki_links(&text)
+                    .into_iter()
+                    .filter_map(|segment| match segment {
+                        Segment::Link(link) => Some(link.target),
+                        Segment::Text(_) => None,
+                    }),
+            ),
+            _ => (),
+        }
+    }
+
+    targets
+}
+
+// Documents that link to each post and page with wiki links, keys are the urls of the linked documents
+pub fn build_backlinks(ctx: &TimugContext) -> HashMap<String, Vec<Backlink>> {
+    let mut sources: Vec<(String, String, Backlink, MarkdownOptions)> = ctx
+        .posts
+        .posts
+        .iter()
+        .chain(ctx.posts.unlisted.iter())
+        .map(|post| (post.content().clone(), post.lang(), Backlink { title: post.title().clone(), url: post.url() }, ctx.config.markdown.merge(&post.markdown())))
+        .collect();
+
+    sources.extend(
+        ctx.pages
+            .items
+            .iter()
+            .filter(|page| page.render && (ctx.draft || !page.draft))
+            .map(|page| (page.content.clone(), page.lang.clone().unwrap_or_default(), Backlink { title: page.title.clone(), url: page.url() }, ctx.config.markdown.merge(&page.markdown))),
+    );
+
+    let mut backlinks: HashMap<String, Vec<Backlink>> = HashMap::new();
+    for (content, lang, source, options) in sources.iter() {
+        for target in wiki_link_targets(content, options) {
+            let Some(target) = resolve(ctx, &target, lang) else {
+                continue;
+            };
+
+            let url = target
+                .url
+                .split_once('#')
+                .map(|(url, _)| url.to_string())
+                .unwrap_or(target.url);
+            let links = backlinks.entry(url.clone()).or_default();
+            if url != source.url && !links.contains(source) {
+                links.push(source.clone());
+            }
+        }
+    }
+
+    backlinks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
+
+    use super::*;
+    use crate::{page::Page, pages::Pages, post::Post, posts::Posts};
+
+    #[test]
+    fn test_split_wiki_links() {
+        assert_eq!(
+            split_wiki_links("See [[Hello World]] and [[notes/rust|Rust notes]]."),
+            vec![
+                Segment::Text("See "),
+                Segment::Link(WikiLink { target: "Hello World".to_string(), label: None }),
+                Segment::Text(" and "),
+                Segment::Link(WikiLink {
+                    target: "notes/rust".to_string(),
+                    label: Some("Rust notes".to_string())
+                }),
+                Segment::Text("."),
+            ]
+        );
+        assert_eq!(split_wiki_links("[[]] and [[open"), vec![Segment::Text("[["), Segment::Text("]] and [[open")]);
+        assert_eq!(split_wiki_links("no links"), vec![Segment::Text("no links")]);
+    }
+
+    #[test]
+    fn test_wiki_link_targets() {
+        let content = "Read [[first-post]].\n\n```\n[[in-code]]\n```\n\nAnd `[[inline]]` with [[Second Post|the second]]";
+        assert_eq!(wiki_link_targets(content, &MarkdownOptions::default()), vec!["first-post".to_string(), "Second Post".to_string()]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut ctx = TimugContext::default();
+        let hidden = Post::load_from_str("---\ntitle: Hidden\ndate: 2024-10-18 12:00:00\nunlisted: true\n---\nHidden", &PathBuf::from("/blog/posts/hidden.md")).unwrap();
+        ctx.posts = Arc::new(Posts { unlisted: vec![Arc::new(hidden)], ..Posts::default() });
+
+        let about = Page::load_from_str("---\ntitle: About\nrender: true\n---\nAbout", Path::new("about.md")).unwrap();
+        let notes = Page::load_from_str("---\ntitle: Notes\nrender: true\ndraft: true\n---\nNotes", Path::new("notes.md")).unwrap();
+        ctx.pages = Arc::new(Pages { items: vec![Arc::new(about), Arc::new(notes)] });
+
+        assert_eq!(resolve(&ctx, "hidden", "").map(|link| link.url).as_deref(), Some("/2024/10/18/hidden.html"));
+        assert_eq!(
+            resolve(&ctx, "About#team", "")
+                .map(|link| link.url)
+                .as_deref(),
+            Some("/about.html#team")
+        );
+        assert!(resolve(&ctx, "notes", "").is_none());
+
+        ctx.draft = true;
+        assert_eq!(resolve(&ctx, "notes", "").map(|link| link.url).as_deref(), Some("/notes.html"));
+    }
+}